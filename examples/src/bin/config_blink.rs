@@ -13,7 +13,7 @@ use embassy_rp::{
 
 use embassy_time::Delay;
 use embedded_hal_async::delay::DelayNs;
-use hlk_ld2450::{Desync, NormalMode, RadarError, LD2450};
+use hlk_ld2450::{DesyncError, LD2450};
 use static_cell::StaticCell;
 use {defmt_rtt as _, panic_probe as _};
 
@@ -47,22 +47,29 @@ async fn main(_spawner: Spawner) {
     // set LED high while we have bluetooth on
     led.set_high();
 
-    // Use the default configuration, but enable bluetooth
+    // Use the default configuration, but enable bluetooth, which restarts the radar
     let radar_config = hlk_ld2450::Config {
-        bluetooth_enabled: true,
+        bluetooth_enabled: Some(true),
         ..Default::default()
     };
 
-    let mut radar = LD2450::new(uart, radar_config).await;
+    let radar = match LD2450::new(uart, radar_config).await {
+        Ok(radar) => radar,
+        Err((_, e)) => panic!("Unable to configure the radar: {:?}", e),
+    };
 
     // wait 15 seconds before turning bluetooth off
     Delay.delay_ms(15000).await;
 
-    if Err(RadarError::Desyncronized) == radar.set_bluetooth_enabled(false).await {
-        // This is a somewhat serious error, we should probably not continue,
-        // or power cycle the radar if possible. However, serial errors are likely
-        // an issue with the hardware or connections
-        panic!("Unable to recover from error during config mode entry/exit");
+    let mut radar = match turn_off_bluetooth(radar).await {
+        Ok(radar) => radar,
+        Err((radar, _)) => match radar.recover().await {
+            Ok(radar) => radar,
+            // This is a somewhat serious error, we should probably not continue,
+            // or power cycle the radar if possible. However, serial errors are likely
+            // an issue with the hardware or connections
+            Err(_) => panic!("Unable to recover from error during config mode entry/exit"),
+        },
     };
 
     loop {
@@ -71,6 +78,21 @@ async fn main(_spawner: Spawner) {
     }
 }
 
+async fn turn_off_bluetooth<S: embedded_io_async::Read + embedded_io_async::Write>(
+    radar: LD2450<S>,
+) -> Result<LD2450<S>, DesyncError<S>> {
+    let mut session = radar.enter_config().await?;
+    match session.set_bluetooth_enabled(false).await {
+        // The setting only takes effect after a restart
+        Ok(()) => session.reboot().await,
+        // A rejected command leaves the radar in configuration mode, so the session can still be ended
+        Err(e) => {
+            defmt::warn!("Unable to turn off bluetooth: {}", defmt::Debug2Format(&e));
+            session.exit_config().await
+        }
+    }
+}
+
 /// Turns on the LED when a target is moving towards the radar
 async fn blink_for_motion<S: embedded_io_async::Read, P: embedded_hal::digital::OutputPin>(
     radar: &mut LD2450<S>,
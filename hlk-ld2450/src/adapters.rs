@@ -0,0 +1,134 @@
+//! Adapters for driving the radar from `std` serial ports, such as USB-UART adapters on Linux.
+//!
+//! [`FromStd`] wraps a blocking [`std::io::Read`] + [`std::io::Write`] port for the
+//! [`blocking`](crate::blocking) driver, and with the `tokio` feature, `FromTokio` wraps a
+//! tokio `AsyncRead` + `AsyncWrite` port for the async driver.
+//!
+//! ```no_run
+//! use hlk_ld2450::{adapters::FromStd, blocking::LD2450};
+//!
+//! let port = std::fs::File::options()
+//!     .read(true)
+//!     .write(true)
+//!     .open("/dev/ttyUSB0")?;
+//! let mut radar = LD2450::new_recycled_config(FromStd::new(port));
+//! let targets = radar.next_radar_targets()?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! The baud rate is changed through the wrapped port, e.g. with a `serialport` closure
+//! implementing [`SetBaudRate`](crate::SetBaudRate):
+//!
+//! ```ignore
+//! let mut host = |port: &mut FromStd<Box<dyn SerialPort>>, baud_rate: BaudRate| {
+//!     port.get_mut().set_baud_rate(baud_rate.bits_per_second())
+//! };
+//! ```
+
+use std::io;
+
+/// Wraps a [`std::io::Read`] + [`std::io::Write`] serial port, for the blocking driver
+#[derive(Debug)]
+pub struct FromStd<T> {
+    inner: T,
+}
+
+impl<T> FromStd<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// The wrapped serial port, e.g. to change its baud rate
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> embedded_io::ErrorType for FromStd<T> {
+    type Error = io::Error;
+}
+
+impl<T: io::Read> embedded_io::Read for FromStd<T> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        loop {
+            match self.inner.read(buf) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                result => return result,
+            }
+        }
+    }
+}
+
+impl<T: io::Write> embedded_io::Write for FromStd<T> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        loop {
+            match self.inner.write(buf) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                result => return written(result, buf),
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        self.inner.flush()
+    }
+}
+
+/// Wraps a tokio `AsyncRead` + `AsyncWrite` serial port, for the async driver
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct FromTokio<T> {
+    inner: T,
+}
+
+#[cfg(feature = "tokio")]
+impl<T> FromTokio<T> {
+    pub fn new(inner: T) -> Self {
+        Self { inner }
+    }
+
+    /// The wrapped serial port, e.g. to change its baud rate
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T> embedded_io_async::ErrorType for FromTokio<T> {
+    type Error = io::Error;
+}
+
+#[cfg(feature = "tokio")]
+impl<T: tokio::io::AsyncRead + Unpin> embedded_io_async::Read for FromTokio<T> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        tokio::io::AsyncReadExt::read(&mut self.inner, buf).await
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl<T: tokio::io::AsyncWrite + Unpin> embedded_io_async::Write for FromTokio<T> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let result = tokio::io::AsyncWriteExt::write(&mut self.inner, buf).await;
+        written(result, buf)
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        tokio::io::AsyncWriteExt::flush(&mut self.inner).await
+    }
+}
+
+/// `embedded-io` forbids writing nothing, which `std` ports report when they are closed
+fn written(result: io::Result<usize>, buf: &[u8]) -> io::Result<usize> {
+    match result {
+        Ok(0) if !buf.is_empty() => Err(io::ErrorKind::WriteZero.into()),
+        result => result,
+    }
+}
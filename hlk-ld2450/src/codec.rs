@@ -0,0 +1,147 @@
+//! A `tokio-util` codec for framing the radar's serial stream, e.g. with `Framed`.
+//!
+//! [`Codec`] decodes the bytes received from the radar into data frames and ACKs,
+//! and encodes [`Command`]s to send to it. Corrupted frames are decoded as errors
+//! without ending the stream, as the decoder resynchronizes on the next header.
+//!
+//! ```
+//! use futures::{SinkExt, StreamExt};
+//! use hlk_ld2450::codec::{Codec, Command};
+//! use tokio_util::codec::Framed;
+//!
+//! # tokio_test::block_on(async {
+//! let serial = tokio_test::io::Builder::new()
+//!     .write(&[
+//!         0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xFF, 0x00, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01,
+//!     ])
+//!     .read(&[
+//!         0xFD, 0xFC, 0xFB, 0xFA, 0x08, 0x00, 0xFF, 0x01, 0x00, 0x00, 0x01, 0x00, 0x40, 0x00,
+//!         0x04, 0x03, 0x02, 0x01,
+//!     ])
+//!     .build();
+//! let mut radar = Framed::new(serial, Codec::new());
+//!
+//! radar.send(Command::EnterConfig).await?;
+//! let ack = radar.next().await.unwrap()?;
+//! # assert!(ack.is_ok());
+//! # Ok::<(), std::io::Error>(())
+//! # }).unwrap();
+//! ```
+
+use std::io;
+
+use bytes::{Buf, BytesMut};
+
+use crate::{
+    config::{BluetoothPassword, FilteringMode, TargetTrackingMode},
+    config_writer::{self, command, SEND_COMMAND_WORD},
+    decoder::{Decoder, Event},
+    BaudRate, RadarError,
+};
+
+/// A configuration command, encoded by [`Codec`]
+///
+/// Most commands are only acknowledged once the radar is in configuration mode,
+/// after [`Command::EnterConfig`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    EnterConfig,
+    ExitConfig,
+    SetTrackingMode(TargetTrackingMode),
+    TrackingMode,
+    FirmwareVersion,
+    /// Stores the baud rate, which takes effect after a restart
+    SetBaudRate(BaudRate),
+    FactoryReset,
+    Restart,
+    SetBluetoothEnabled(bool),
+    MacAddress,
+    BluetoothPermission(BluetoothPassword),
+    SetBluetoothPassword(BluetoothPassword),
+    ZoneFiltering,
+    SetZoneFiltering(FilteringMode),
+}
+
+impl Command {
+    /// The command word, which the radar echoes in its ACK with the ACK bit (0x0100) set
+    pub fn word(&self) -> u16 {
+        match self {
+            Command::EnterConfig => command::ENTER_CONFIG,
+            Command::ExitConfig => command::EXIT_CONFIG,
+            Command::SetTrackingMode(mode) => config_writer::tracking_mode_command(*mode),
+            Command::TrackingMode => command::GET_TRACKING_MODE,
+            Command::FirmwareVersion => command::GET_FIRMWARE_VERSION,
+            Command::SetBaudRate(_) => command::SET_BAUD_RATE,
+            Command::FactoryReset => command::FACTORY_RESTORE,
+            Command::Restart => command::RESTART,
+            Command::SetBluetoothEnabled(_) => command::SET_BLUETOOTH,
+            Command::MacAddress => command::GET_MAC_ADDRESS,
+            Command::BluetoothPermission(_) => command::GET_BLUETOOTH_PERMISSION,
+            Command::SetBluetoothPassword(_) => command::SET_BLUETOOTH_PASSWORD,
+            Command::ZoneFiltering => command::GET_ZONE_FILTERING,
+            Command::SetZoneFiltering(_) => command::SET_ZONE_FILTERING,
+        }
+    }
+
+    /// Encodes the command frame, passing each part of it to `write`
+    fn encode(&self, write: impl FnMut(&[u8])) {
+        let data = |value: u16| value.to_le_bytes();
+        match self {
+            Command::EnterConfig | Command::MacAddress => {
+                config_writer::encode_command(self.word(), &SEND_COMMAND_WORD, write)
+            }
+            Command::SetBaudRate(baud_rate) => {
+                config_writer::encode_command(self.word(), &data(baud_rate.byte_repr()), write)
+            }
+            Command::SetBluetoothEnabled(enabled) => {
+                config_writer::encode_command(self.word(), &data(u16::from(*enabled)), write)
+            }
+            Command::BluetoothPermission(password) | Command::SetBluetoothPassword(password) => {
+                config_writer::encode_command(self.word(), password.as_bytes(), write)
+            }
+            Command::SetZoneFiltering(mode) => {
+                config_writer::encode_command(self.word(), &mode.to_bytes(), write)
+            }
+            _ => config_writer::encode_command(self.word(), &[], write),
+        }
+    }
+}
+
+/// Decodes data frames and ACKs, and encodes [`Command`]s
+#[derive(Debug, Clone, Default)]
+pub struct Codec {
+    decoder: Decoder,
+}
+
+impl Codec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl tokio_util::codec::Decoder for Codec {
+    /// A frame, or the reason it could not be decoded
+    type Item = Result<Event, RadarError>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // Partial frames are kept by the decoder, so every byte can be consumed
+        for (i, &byte) in src.iter().enumerate() {
+            if let Some(event) = self.decoder.push(byte) {
+                src.advance(i + 1);
+                return Ok(Some(event));
+            }
+        }
+        src.clear();
+        Ok(None)
+    }
+}
+
+impl tokio_util::codec::Encoder<Command> for Codec {
+    type Error = io::Error;
+
+    fn encode(&mut self, command: Command, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        command.encode(|part| dst.extend_from_slice(part));
+        Ok(())
+    }
+}
@@ -1,7 +1,13 @@
+use core::fmt;
+
+use crate::RadarError;
+
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct Config {
     pub tracking: TargetTrackingMode,
-    pub bluetooth_enabled: bool,
+    /// `None` leaves the Bluetooth setting unchanged when the config is applied,
+    /// and is reported when the setting can't be read back from the radar
+    pub bluetooth_enabled: Option<bool>,
     pub filtering_mode: FilteringMode,
 }
 
@@ -31,3 +37,318 @@ pub struct FilteredRegion {
     x_end: i16,
     y_end: i16,
 }
+
+/// The reason a [`FilteredRegion`] could not be created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegionError {
+    /// A vertex lies outside the radar's detection range
+    OutOfRange,
+    /// The vertices share an x or y coordinate, so the region has no area
+    Empty,
+}
+
+/// The password protecting the radar's Bluetooth connection: 6 printable ASCII characters,
+/// without spaces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BluetoothPassword([u8; 6]);
+
+/// The reason a [`BluetoothPassword`] could not be created
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordError {
+    /// The password is not exactly 6 characters long
+    Length,
+    /// The password contains a character other than printable ASCII
+    Charset,
+}
+
+/// The setting that could not be applied while initializing the radar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigError {
+    /// The radar could not be switched into or out of configuration mode
+    ModeSwitch(RadarError),
+    /// The radar could not be restarted to apply the Bluetooth setting
+    Restart(RadarError),
+    TrackingMode(RadarError),
+    Bluetooth(RadarError),
+    ZoneFiltering(RadarError),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigError::ModeSwitch(_) => "could not switch configuration mode",
+            ConfigError::Restart(_) => "could not restart the radar",
+            ConfigError::TrackingMode(_) => "could not set the tracking mode",
+            ConfigError::Bluetooth(_) => "could not set Bluetooth",
+            ConfigError::ZoneFiltering(_) => "could not set zone filtering",
+        })
+    }
+}
+
+impl fmt::Display for RegionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegionError::OutOfRange => f.write_str("vertex outside the detection range"),
+            RegionError::Empty => f.write_str("region without area"),
+        }
+    }
+}
+
+impl fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordError::Length => f.write_str("password is not 6 characters long"),
+            PasswordError::Charset => f.write_str("password is not printable ASCII"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ConfigError::ModeSwitch(e)
+            | ConfigError::Restart(e)
+            | ConfigError::TrackingMode(e)
+            | ConfigError::Bluetooth(e)
+            | ConfigError::ZoneFiltering(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for RegionError {}
+
+#[cfg(feature = "std")]
+impl std::error::Error for PasswordError {}
+
+impl FilteringMode {
+    /// Encodes the filtering mode as the payload of the zone filtering command:
+    /// the filtering type followed by the diagonal vertices of each of the 3 regions
+    pub(crate) fn to_bytes(&self) -> [u8; 26] {
+        let (filtering_type, regions): (u16, &[FilteredRegion]) = match self {
+            FilteringMode::None => (0x0000, &[]),
+            // The radar only detects targets inside the regions
+            FilteringMode::Outside(regions) => (0x0001, regions),
+            // The radar does not detect targets inside the regions
+            FilteringMode::Inside(regions) => (0x0002, regions),
+        };
+
+        let mut bytes = [0; 26];
+        bytes[0..2].copy_from_slice(&filtering_type.to_le_bytes());
+        for (region, chunk) in regions.iter().zip(bytes[2..].chunks_exact_mut(8)) {
+            chunk.copy_from_slice(&region.to_bytes());
+        }
+        bytes
+    }
+
+    /// Decodes the payload of the zone filtering query, or `None` if the filtering type is unknown
+    pub(crate) fn from_bytes(bytes: &[u8; 26]) -> Option<Self> {
+        let mut regions = heapless::Vec::new();
+        for chunk in bytes[2..].chunks_exact(8) {
+            // unused regions are left zeroed by the radar
+            if chunk.iter().any(|&byte| byte != 0) {
+                // Safety: there are exactly 3 regions in the payload
+                unsafe { regions.push_unchecked(FilteredRegion::from_bytes(chunk)) };
+            }
+        }
+
+        match u16::from_le_bytes([bytes[0], bytes[1]]) {
+            0x0000 => Some(FilteringMode::None),
+            0x0001 => Some(FilteringMode::Outside(regions)),
+            0x0002 => Some(FilteringMode::Inside(regions)),
+            _ => None,
+        }
+    }
+}
+
+impl FilteredRegion {
+    /// The furthest a vertex can be to either side of the sensor, in mm
+    pub const MAX_X: i16 = 6000;
+    /// The furthest a vertex can be in front of the sensor, in mm
+    pub const MAX_Y: i16 = 6000;
+
+    /// Creates a region from two diagonal vertices, in mm.
+    ///
+    /// Vertices must lie within the detection range, `-MAX_X..=MAX_X` by `0..=MAX_Y`,
+    /// and the region must have a non-zero area.
+    pub fn new(x_start: i16, y_start: i16, x_end: i16, y_end: i16) -> Result<Self, RegionError> {
+        let x_range = -Self::MAX_X..=Self::MAX_X;
+        let y_range = 0..=Self::MAX_Y;
+        if ![x_start, x_end].iter().all(|x| x_range.contains(x))
+            || ![y_start, y_end].iter().all(|y| y_range.contains(y))
+        {
+            return Err(RegionError::OutOfRange);
+        }
+        if x_start == x_end || y_start == y_end {
+            return Err(RegionError::Empty);
+        }
+        Ok(Self {
+            x_start,
+            y_start,
+            x_end,
+            y_end,
+        })
+    }
+
+    /// The x coordinate of the first vertex, in mm
+    pub fn x_start(&self) -> i16 {
+        self.x_start
+    }
+
+    /// The y coordinate of the first vertex, in mm
+    pub fn y_start(&self) -> i16 {
+        self.y_start
+    }
+
+    /// The x coordinate of the second vertex, in mm
+    pub fn x_end(&self) -> i16 {
+        self.x_end
+    }
+
+    /// The y coordinate of the second vertex, in mm
+    pub fn y_end(&self) -> i16 {
+        self.y_end
+    }
+
+    /// Whether a point, in mm, lies within the region, including its edges
+    pub fn contains(&self, x: i16, y: i16) -> bool {
+        let (x_min, x_max) = (self.x_start.min(self.x_end), self.x_start.max(self.x_end));
+        let (y_min, y_max) = (self.y_start.min(self.y_end), self.y_start.max(self.y_end));
+        (x_min..=x_max).contains(&x) && (y_min..=y_max).contains(&y)
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Self {
+        Self {
+            x_start: i16::from_le_bytes([bytes[0], bytes[1]]),
+            y_start: i16::from_le_bytes([bytes[2], bytes[3]]),
+            x_end: i16::from_le_bytes([bytes[4], bytes[5]]),
+            y_end: i16::from_le_bytes([bytes[6], bytes[7]]),
+        }
+    }
+
+    fn to_bytes(self) -> [u8; 8] {
+        let mut bytes = [0; 8];
+        bytes[0..2].copy_from_slice(&self.x_start.to_le_bytes());
+        bytes[2..4].copy_from_slice(&self.y_start.to_le_bytes());
+        bytes[4..6].copy_from_slice(&self.x_end.to_le_bytes());
+        bytes[6..8].copy_from_slice(&self.y_end.to_le_bytes());
+        bytes
+    }
+}
+
+impl BluetoothPassword {
+    /// The password radars are shipped with
+    pub const FACTORY: BluetoothPassword = BluetoothPassword(*b"HiLink");
+
+    pub fn new(password: &str) -> Result<Self, PasswordError> {
+        if !password.bytes().all(|byte| byte.is_ascii_graphic()) {
+            return Err(PasswordError::Charset);
+        }
+        let bytes = password
+            .as_bytes()
+            .try_into()
+            .map_err(|_| PasswordError::Length)?;
+        Ok(Self(bytes))
+    }
+
+    pub fn as_str(&self) -> &str {
+        // Safety: only ASCII characters are accepted
+        unsafe { core::str::from_utf8_unchecked(&self.0) }
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8; 6] {
+        &self.0
+    }
+}
+
+impl Default for BluetoothPassword {
+    fn default() -> Self {
+        Self::FACTORY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_validation() {
+        assert!(FilteredRegion::new(-1000, 0, 1000, 2000).is_ok());
+        assert!(FilteredRegion::new(6000, 6000, -6000, 0).is_ok());
+        assert_eq!(
+            FilteredRegion::new(-6001, 0, 1000, 2000),
+            Err(RegionError::OutOfRange)
+        );
+        assert_eq!(
+            FilteredRegion::new(-1000, -1, 1000, 2000),
+            Err(RegionError::OutOfRange)
+        );
+        assert_eq!(
+            FilteredRegion::new(-1000, 0, 1000, 6001),
+            Err(RegionError::OutOfRange)
+        );
+        assert_eq!(
+            FilteredRegion::new(500, 0, 500, 2000),
+            Err(RegionError::Empty)
+        );
+    }
+
+    #[test]
+    fn test_bluetooth_password_validation() {
+        assert_eq!(
+            BluetoothPassword::new("HiLink"),
+            Ok(BluetoothPassword::FACTORY)
+        );
+        assert_eq!(BluetoothPassword::new("s3cr#t").unwrap().as_str(), "s3cr#t");
+        assert_eq!(BluetoothPassword::new("short"), Err(PasswordError::Length));
+        assert_eq!(
+            BluetoothPassword::new("toolong"),
+            Err(PasswordError::Length)
+        );
+        assert_eq!(
+            BluetoothPassword::new("pass 1"),
+            Err(PasswordError::Charset)
+        );
+        assert_eq!(BluetoothPassword::new("pässw"), Err(PasswordError::Charset));
+    }
+
+    #[test]
+    fn test_region_contains() {
+        let region = FilteredRegion::new(1000, 2000, -1000, 0).unwrap();
+        assert!(region.contains(0, 1000));
+        assert!(region.contains(-1000, 2000));
+        assert!(!region.contains(1001, 1000));
+        assert!(!region.contains(0, 2001));
+    }
+
+    #[test]
+    fn test_filtering_mode_encoding() {
+        let regions = [
+            FilteredRegion::new(-1000, 0, 1000, 2000).unwrap(),
+            FilteredRegion::new(-6000, 5000, -5000, 6000).unwrap(),
+        ];
+        let mode = FilteringMode::Inside(heapless::Vec::from_slice(&regions).unwrap());
+
+        let bytes = mode.to_bytes();
+        assert_eq!(
+            bytes,
+            [
+                0x02, 0x00, 0x18, 0xFC, 0x00, 0x00, 0xE8, 0x03, 0xD0, 0x07, 0x90, 0xE8, 0x88, 0x13,
+                0x78, 0xEC, 0x70, 0x17, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            ]
+        );
+        assert_eq!(FilteringMode::from_bytes(&bytes), Some(mode));
+    }
+
+    #[test]
+    fn test_filtering_mode_unknown_type() {
+        let mut bytes = [0; 26];
+        bytes[0] = 0x03;
+        assert_eq!(FilteringMode::from_bytes(&bytes), None);
+        assert_eq!(
+            FilteringMode::from_bytes(&FilteringMode::None.to_bytes()),
+            Some(FilteringMode::None)
+        );
+    }
+}
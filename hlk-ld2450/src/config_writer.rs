@@ -1,121 +1,344 @@
-use embedded_io_async::{Read, ReadExactError, Write};
+use crate::{config::TargetTrackingMode, decoder::Ack, ProtocolError};
 
 const COMMAND_HEADER: [u8; 4] = [0xFD, 0xFC, 0xFB, 0xFA];
 const END_OF_FRAME: [u8; 4] = [0x04, 0x03, 0x02, 0x01];
-const SEND_COMMAND_WORD: [u8; 2] = [0x01, 0x00];
+pub(crate) const SEND_COMMAND_WORD: [u8; 2] = [0x01, 0x00];
 
-pub(crate) async fn enter_config_mode<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command_data(writer, 0x01, 0x01).await
-}
+/// The radar echoes the command word with this bit set in its ACK
+const ACK_COMMAND_FLAG: u16 = 0x0100;
+/// The status word of an ACK for a command that was applied
+const ACK_STATUS_SUCCESS: u16 = 0x0000;
 
-pub(crate) async fn exit_config_mode<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command(writer, 0xFE).await
+/// The command words sent to the radar, shared by the driver and the codec
+pub(crate) mod command {
+    pub(crate) const ENTER_CONFIG: u16 = 0xFF;
+    pub(crate) const EXIT_CONFIG: u16 = 0xFE;
+    pub(crate) const SINGLE_TARGET_TRACKING: u16 = 0x80;
+    pub(crate) const MULTI_TARGET_TRACKING: u16 = 0x90;
+    pub(crate) const GET_TRACKING_MODE: u16 = 0x91;
+    pub(crate) const GET_FIRMWARE_VERSION: u16 = 0xA0;
+    pub(crate) const SET_BAUD_RATE: u16 = 0xA1;
+    pub(crate) const FACTORY_RESTORE: u16 = 0xA2;
+    pub(crate) const RESTART: u16 = 0xA3;
+    pub(crate) const SET_BLUETOOTH: u16 = 0xA4;
+    pub(crate) const GET_MAC_ADDRESS: u16 = 0xA5;
+    pub(crate) const GET_BLUETOOTH_PERMISSION: u16 = 0xA8;
+    pub(crate) const SET_BLUETOOTH_PASSWORD: u16 = 0xA9;
+    pub(crate) const GET_ZONE_FILTERING: u16 = 0xC1;
+    pub(crate) const SET_ZONE_FILTERING: u16 = 0xC2;
 }
 
-pub(crate) async fn set_single_target_tracking<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command(writer, 0x80).await
+/// The command word that switches the radar to `mode`
+pub(crate) fn tracking_mode_command(mode: TargetTrackingMode) -> u16 {
+    match mode {
+        TargetTrackingMode::Single => command::SINGLE_TARGET_TRACKING,
+        TargetTrackingMode::Multiple => command::MULTI_TARGET_TRACKING,
+    }
 }
 
-pub(crate) async fn set_multi_target_tracking<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command(writer, 0x90).await
+/// Whether `ack` is a reply to `command`
+pub(crate) fn acknowledges(ack: &Ack, command: u16) -> bool {
+    ack.command == command | ACK_COMMAND_FLAG
 }
 
-pub(crate) async fn get_target_tracking_mode<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command(writer, 0x91).await
+pub(crate) fn is_probe_ack(ack: &Ack) -> bool {
+    acknowledges(ack, command::GET_TRACKING_MODE)
 }
 
-pub(crate) async fn get_firmware_version<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command(writer, 0xA0).await
+/// Encodes a command frame without a transport, passing each part of it to `write`
+#[cfg(feature = "codec")]
+pub(crate) fn encode_command(command: u16, data: &[u8], mut write: impl FnMut(&[u8])) {
+    let msg_len = 2 + data.len() as u16;
+    write(&COMMAND_HEADER);
+    write(&msg_len.to_le_bytes());
+    write(&command.to_le_bytes());
+    write(data);
+    write(&END_OF_FRAME);
 }
 
-pub(crate) async fn set_baud_rate<W: Write>(
-    writer: &mut W,
-    baud_rate: crate::BaudRate,
-) -> Result<(), W::Error> {
-    write_command_data(writer, 0xA1, baud_rate.byte_repr()).await
-}
+/// The commands, generated as async functions and, with the `blocking` feature,
+/// as blocking functions in `config_writer::blocking`
+#[maybe_async_cfg::maybe(
+    idents(asynch(sync = "blocking", async = "asynch")),
+    sync(feature = "blocking"),
+    async()
+)]
+pub(crate) mod asynch {
+    use super::{ack, COMMAND_HEADER, END_OF_FRAME, SEND_COMMAND_WORD};
+    pub(crate) use super::{acknowledges, command, is_probe_ack, tracking_mode_command};
+    use crate::{
+        config::{BluetoothPassword, FilteringMode, TargetTrackingMode},
+        decoder::Ack,
+        FirmwareVersion, MacAddress, ProtocolError,
+    };
 
-pub(crate) async fn factory_restore<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command(writer, 0xA2).await
-}
+    /// A serial link to the radar that commands are written to, and ACKs read from
+    pub(crate) trait Transport {
+        async fn write_all(&mut self, data: &[u8]) -> Result<(), ProtocolError>;
 
-pub(crate) async fn restart<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command(writer, 0xA3).await
-}
+        /// Reads the next ACK for `command`, discarding any data frames received before it,
+        /// and late ACKs for earlier commands, e.g. an attempt that timed out
+        async fn read_ack(&mut self, command: u16) -> Result<Ack, ProtocolError>;
+    }
 
-pub(crate) async fn set_bluetooth_enabled<W: Write>(
-    writer: &mut W,
-    enabled: bool,
-) -> Result<(), W::Error> {
-    let data = if enabled { 0x01 } else { 0x00 };
-    write_command_data(writer, 0xA4, data).await
-}
+    pub(crate) async fn enter_config_mode<T: Transport>(
+        transport: &mut T,
+    ) -> Result<(), ProtocolError> {
+        write_command_data(
+            transport,
+            command::ENTER_CONFIG,
+            u16::from_le_bytes(SEND_COMMAND_WORD),
+        )
+        .await?;
+        // protocol version and buffer size, neither of which is used
+        ack::<4>(
+            transport.read_ack(command::ENTER_CONFIG).await?,
+            command::ENTER_CONFIG,
+        )?;
+        Ok(())
+    }
 
-pub(crate) async fn get_mac_address<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command_data(writer, 0xA5, 0x01).await
-}
+    pub(crate) async fn exit_config_mode<T: Transport>(
+        transport: &mut T,
+    ) -> Result<(), ProtocolError> {
+        send_command(transport, command::EXIT_CONFIG).await
+    }
 
-pub(crate) async fn get_zone_filtering<W: Write>(writer: &mut W) -> Result<(), W::Error> {
-    write_command_data(writer, 0xC1, 0x01).await
-}
+    pub(crate) async fn set_target_tracking_mode<T: Transport>(
+        transport: &mut T,
+        mode: TargetTrackingMode,
+    ) -> Result<(), ProtocolError> {
+        send_command(transport, tracking_mode_command(mode)).await
+    }
 
-pub(crate) async fn set_zone_filtering<W: Write>(
-    writer: &mut W,
-    zone_filtering: &[u8],
-) -> Result<(), W::Error> {
-    let msg_len = 2 + zone_filtering.len() as u16;
-    writer.write_all(&COMMAND_HEADER).await?;
-    writer.write_all(&msg_len.to_le_bytes()).await?;
-    writer.write_all(&0xC2u16.to_le_bytes()).await?;
-    writer.write_all(zone_filtering).await?;
-    writer.write_all(&END_OF_FRAME).await
-}
+    pub(crate) async fn get_target_tracking_mode<T: Transport>(
+        transport: &mut T,
+        _: (),
+    ) -> Result<TargetTrackingMode, ProtocolError> {
+        write_command(transport, command::GET_TRACKING_MODE).await?;
+        let data = ack::<2>(
+            transport.read_ack(command::GET_TRACKING_MODE).await?,
+            command::GET_TRACKING_MODE,
+        )?;
+        match u16::from_le_bytes(data) {
+            0x0001 => Ok(TargetTrackingMode::Single),
+            0x0002 => Ok(TargetTrackingMode::Multiple),
+            _ => Err(ProtocolError::InvalidData),
+        }
+    }
 
-pub(crate) async fn write_command<W: Write>(writer: &mut W, command: u16) -> Result<(), W::Error> {
-    writer.write_all(&COMMAND_HEADER).await?;
-    writer.write_all(&2u16.to_le_bytes()).await?;
-    writer.write_all(&command.to_le_bytes()).await?;
-    writer.write_all(&END_OF_FRAME).await
-}
+    /// Sends a tracking mode query without waiting for the ACK, which is only
+    /// sent if the radar is in configuration mode
+    pub(crate) async fn write_probe<T: Transport>(transport: &mut T) -> Result<(), ProtocolError> {
+        write_command(transport, command::GET_TRACKING_MODE).await
+    }
 
-pub(crate) async fn write_command_data<W: Write>(
-    writer: &mut W,
-    command: u16,
-    data: u16,
-) -> Result<(), W::Error> {
-    writer.write_all(&COMMAND_HEADER).await?;
-    writer.write_all(&4u16.to_le_bytes()).await?;
-    writer.write_all(&command.to_le_bytes()).await?;
-    writer.write_all(&data.to_le_bytes()).await?;
-    writer.write_all(&END_OF_FRAME).await
-}
+    pub(crate) async fn get_firmware_version<T: Transport>(
+        transport: &mut T,
+        _: (),
+    ) -> Result<FirmwareVersion, ProtocolError> {
+        write_command(transport, command::GET_FIRMWARE_VERSION).await?;
+        let data = ack::<8>(
+            transport.read_ack(command::GET_FIRMWARE_VERSION).await?,
+            command::GET_FIRMWARE_VERSION,
+        )?;
+        Ok(FirmwareVersion::from(&data))
+    }
+
+    pub(crate) async fn set_baud_rate<T: Transport>(
+        transport: &mut T,
+        baud_rate: crate::BaudRate,
+    ) -> Result<(), ProtocolError> {
+        send_command_data(transport, command::SET_BAUD_RATE, baud_rate.byte_repr()).await
+    }
+
+    pub(crate) async fn factory_restore<T: Transport>(
+        transport: &mut T,
+    ) -> Result<(), ProtocolError> {
+        send_command(transport, command::FACTORY_RESTORE).await
+    }
+
+    pub(crate) async fn restart<T: Transport>(transport: &mut T) -> Result<(), ProtocolError> {
+        send_command(transport, command::RESTART).await
+    }
+
+    pub(crate) async fn set_bluetooth_enabled<T: Transport>(
+        transport: &mut T,
+        enabled: bool,
+    ) -> Result<(), ProtocolError> {
+        let data = if enabled { 0x01 } else { 0x00 };
+        send_command_data(transport, command::SET_BLUETOOTH, data).await
+    }
+
+    pub(crate) async fn get_mac_address<T: Transport>(
+        transport: &mut T,
+        _: (),
+    ) -> Result<MacAddress, ProtocolError> {
+        write_command_data(
+            transport,
+            command::GET_MAC_ADDRESS,
+            u16::from_le_bytes(SEND_COMMAND_WORD),
+        )
+        .await?;
+        let data = ack::<6>(
+            transport.read_ack(command::GET_MAC_ADDRESS).await?,
+            command::GET_MAC_ADDRESS,
+        )?;
+        Ok(MacAddress::from(data))
+    }
+
+    pub(crate) async fn get_zone_filtering<T: Transport>(
+        transport: &mut T,
+        _: (),
+    ) -> Result<FilteringMode, ProtocolError> {
+        write_command(transport, command::GET_ZONE_FILTERING).await?;
+        let data = ack::<26>(
+            transport.read_ack(command::GET_ZONE_FILTERING).await?,
+            command::GET_ZONE_FILTERING,
+        )?;
+        FilteringMode::from_bytes(&data).ok_or(ProtocolError::InvalidData)
+    }
+
+    pub(crate) async fn set_zone_filtering<T: Transport>(
+        transport: &mut T,
+        filtering_mode: &FilteringMode,
+    ) -> Result<(), ProtocolError> {
+        write_command_bytes(
+            transport,
+            command::SET_ZONE_FILTERING,
+            &filtering_mode.to_bytes(),
+        )
+        .await?;
+        ack::<0>(
+            transport.read_ack(command::SET_ZONE_FILTERING).await?,
+            command::SET_ZONE_FILTERING,
+        )?;
+        Ok(())
+    }
 
-pub(crate) async fn ack<const N: usize, R: Read>(
-    reader: &mut R,
-    buf: &mut [u8; N],
-) -> Result<(), ReadExactError<R::Error>> {
-    let mut temp = [0; 4];
-    reader.read_exact(&mut temp).await?;
-    if temp != COMMAND_HEADER {
-        // TODO: real error
-        return Err(ReadExactError::UnexpectedEof);
+    pub(crate) async fn set_bluetooth_password<T: Transport>(
+        transport: &mut T,
+        password: &BluetoothPassword,
+    ) -> Result<(), ProtocolError> {
+        write_command_bytes(
+            transport,
+            command::SET_BLUETOOTH_PASSWORD,
+            password.as_bytes(),
+        )
+        .await?;
+        ack::<0>(
+            transport.read_ack(command::SET_BLUETOOTH_PASSWORD).await?,
+            command::SET_BLUETOOTH_PASSWORD,
+        )?;
+        Ok(())
     }
 
-    reader.read_exact(&mut temp[0..2]).await?;
-    let length = u16::from_le_bytes([temp[0], temp[1]]);
-    if length != N as u16 {
-        // TODO: real error
-        return Err(ReadExactError::UnexpectedEof);
+    /// Returns whether the radar accepted the password
+    pub(crate) async fn get_bluetooth_permission<T: Transport>(
+        transport: &mut T,
+        password: &BluetoothPassword,
+    ) -> Result<bool, ProtocolError> {
+        write_command_bytes(
+            transport,
+            command::GET_BLUETOOTH_PERMISSION,
+            password.as_bytes(),
+        )
+        .await?;
+        match ack::<0>(
+            transport
+                .read_ack(command::GET_BLUETOOTH_PERMISSION)
+                .await?,
+            command::GET_BLUETOOTH_PERMISSION,
+        ) {
+            Ok(_) => Ok(true),
+            Err(ProtocolError::CommandFailed { .. }) => Ok(false),
+            Err(e) => Err(e),
+        }
     }
 
-    reader.read_exact(&mut buf[..]).await?;
+    /// Sends a command without data, and waits for an ACK without a payload
+    async fn send_command<T: Transport>(
+        transport: &mut T,
+        command: u16,
+    ) -> Result<(), ProtocolError> {
+        write_command(transport, command).await?;
+        ack::<0>(transport.read_ack(command).await?, command)?;
+        Ok(())
+    }
+
+    /// Sends a command with data, and waits for an ACK without a payload
+    async fn send_command_data<T: Transport>(
+        transport: &mut T,
+        command: u16,
+        data: u16,
+    ) -> Result<(), ProtocolError> {
+        write_command_data(transport, command, data).await?;
+        ack::<0>(transport.read_ack(command).await?, command)?;
+        Ok(())
+    }
+
+    pub(crate) async fn write_command<T: Transport>(
+        transport: &mut T,
+        command: u16,
+    ) -> Result<(), ProtocolError> {
+        transport.write_all(&COMMAND_HEADER).await?;
+        transport.write_all(&2u16.to_le_bytes()).await?;
+        transport.write_all(&command.to_le_bytes()).await?;
+        transport.write_all(&END_OF_FRAME).await
+    }
+
+    pub(crate) async fn write_command_data<T: Transport>(
+        transport: &mut T,
+        command: u16,
+        data: u16,
+    ) -> Result<(), ProtocolError> {
+        transport.write_all(&COMMAND_HEADER).await?;
+        transport.write_all(&4u16.to_le_bytes()).await?;
+        transport.write_all(&command.to_le_bytes()).await?;
+        transport.write_all(&data.to_le_bytes()).await?;
+        transport.write_all(&END_OF_FRAME).await
+    }
+
+    async fn write_command_bytes<T: Transport>(
+        transport: &mut T,
+        command: u16,
+        data: &[u8],
+    ) -> Result<(), ProtocolError> {
+        let msg_len = 2 + data.len() as u16;
+        transport.write_all(&COMMAND_HEADER).await?;
+        transport.write_all(&msg_len.to_le_bytes()).await?;
+        transport.write_all(&command.to_le_bytes()).await?;
+        transport.write_all(data).await?;
+        transport.write_all(&END_OF_FRAME).await
+    }
+}
+
+/// Checks that `ack` is a successful reply to `command`, returning the `N` byte
+/// payload that follows the status word.
+pub(crate) fn ack<const N: usize>(ack: Ack, command: u16) -> Result<[u8; N], ProtocolError> {
+    if ack.command != command | ACK_COMMAND_FLAG {
+        return Err(ProtocolError::UnexpectedCommand {
+            expected: command | ACK_COMMAND_FLAG,
+            actual: ack.command,
+        });
+    }
+
+    if ack.status != ACK_STATUS_SUCCESS {
+        return Err(ProtocolError::CommandFailed {
+            command,
+            status: ack.status,
+        });
+    }
 
-    // EOF
-    reader.read_exact(&mut temp).await?;
-    if temp != END_OF_FRAME {
-        // TODO: real error
-        return Err(ReadExactError::UnexpectedEof);
+    // the length includes the command word and status
+    if ack.data.len() != N {
+        return Err(ProtocolError::LengthMismatch {
+            expected: N as u16 + 4,
+            actual: ack.data.len() as u16 + 4,
+        });
     }
 
-    Ok(())
+    let mut buf = [0; N];
+    buf.copy_from_slice(&ack.data);
+    Ok(buf)
 }
@@ -0,0 +1,224 @@
+//! A push-based decoder for the frames sent by the radar, independent of any serial port.
+//!
+//! Bytes can be fed in chunks of any size, from DMA ring buffers, interrupt handlers or files.
+//!
+//! ```
+//! use hlk_ld2450::decoder::{Decoder, Event};
+//!
+//! let mut decoder = Decoder::new();
+//! let data = [
+//!     0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01, 0x00, 0x00, 0x00,
+//!     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x55, 0xCC,
+//! ];
+//! for event in decoder.feed(&data) {
+//!     if let Ok(Event::Targets([Some(target), _, _])) = event {
+//!         assert_eq!(target.x_coordinate, -782);
+//!     }
+//! }
+//! ```
+
+use crate::{
+    radar_target::decode_radar_targets, ProtocolError, RadarError, RadarTarget, RADAR_ACK_EOF,
+    RADAR_ACK_HEADER, RADAR_DATA_EOF, RADAR_DATA_FRAME_SIZE, RADAR_DATA_HEADER,
+};
+
+/// The largest ACK payload, returned when querying zone filtering
+pub const MAX_ACK_PAYLOAD: usize = 26;
+/// The largest ACK length, which includes the command word and status
+const MAX_ACK_LENGTH: usize = MAX_ACK_PAYLOAD + 4;
+/// The largest number of bytes following a header, which is the longest ACK and its EOF
+pub(crate) const MAX_FRAME_BODY: usize = MAX_ACK_LENGTH + RADAR_ACK_EOF.len();
+
+/// A complete frame received from the radar
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    /// The radar's three target slots, with untracked slots left empty
+    Targets([Option<RadarTarget>; 3]),
+    /// The response to a configuration command
+    Ack(Ack),
+}
+
+/// The radar's response to a configuration command
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ack {
+    /// The command word of the acknowledged command, with the ACK bit (0x0100) set
+    pub command: u16,
+    /// 0 if the command succeeded
+    pub status: u16,
+    /// The data following the status
+    pub data: heapless::Vec<u8, MAX_ACK_PAYLOAD>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Looking for either header, tracking how many bytes of each have matched
+    Seek { data: usize, ack: usize },
+    /// Reading the targets and EOF of a data frame
+    DataBody,
+    /// Reading the length of an ACK
+    AckLength,
+    /// Reading the content and EOF of an ACK
+    AckBody { length: usize },
+}
+
+/// Decodes data frames and ACKs from the radar's serial output
+#[derive(Debug, Clone, PartialEq)]
+pub struct Decoder {
+    state: State,
+    buf: [u8; MAX_FRAME_BODY],
+    len: usize,
+}
+
+impl Default for Decoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Decoder {
+    pub const fn new() -> Self {
+        Self {
+            state: State::Seek { data: 0, ack: 0 },
+            buf: [0; MAX_FRAME_BODY],
+            len: 0,
+        }
+    }
+
+    /// Discards any partially decoded frame
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+
+    /// The number of bytes required to complete the current frame, or 1 when looking for
+    /// the start of a frame.
+    ///
+    /// Reading at most this many bytes at a time guarantees that a frame is completed
+    /// by the last byte read, so no bytes of the next frame need to be kept around.
+    pub fn bytes_needed(&self) -> usize {
+        match self.state {
+            State::Seek { .. } => 1,
+            State::DataBody => RADAR_DATA_FRAME_SIZE + RADAR_DATA_EOF.len() - self.len,
+            State::AckLength => 2 - self.len,
+            State::AckBody { length } => length + RADAR_ACK_EOF.len() - self.len,
+        }
+    }
+
+    /// Returns an iterator over the frames completed by `data`.
+    ///
+    /// Bytes left over after the last complete frame are kept, and continue
+    /// to be decoded by the next call.
+    pub fn feed<'a>(&'a mut self, data: &'a [u8]) -> Events<'a> {
+        Events {
+            decoder: self,
+            data,
+        }
+    }
+
+    /// Decodes a single byte, returning the frame it completes, if any.
+    ///
+    /// Frames with a bad EOF are reported as errors, after which the decoder
+    /// looks for the next header.
+    pub fn push(&mut self, byte: u8) -> Option<Result<Event, RadarError>> {
+        match self.state {
+            State::Seek { data, ack } => {
+                let data = seek(&RADAR_DATA_HEADER, data, byte);
+                let ack = seek(&RADAR_ACK_HEADER, ack, byte);
+                self.len = 0;
+                self.state = if data == RADAR_DATA_HEADER.len() {
+                    State::DataBody
+                } else if ack == RADAR_ACK_HEADER.len() {
+                    State::AckLength
+                } else {
+                    State::Seek { data, ack }
+                };
+                None
+            }
+            State::DataBody => {
+                self.buffer(byte);
+                if self.bytes_needed() > 0 {
+                    return None;
+                }
+                self.state = State::Seek { data: 0, ack: 0 };
+                if self.buf[RADAR_DATA_FRAME_SIZE..self.len] != RADAR_DATA_EOF {
+                    return Some(Err(RadarError::UnexpectedFrameSize));
+                }
+                let mut frame = [0; RADAR_DATA_FRAME_SIZE];
+                frame.copy_from_slice(&self.buf[..RADAR_DATA_FRAME_SIZE]);
+                Some(decode_radar_targets(&frame).map(Event::Targets))
+            }
+            State::AckLength => {
+                self.buffer(byte);
+                if self.bytes_needed() > 0 {
+                    return None;
+                }
+                let length = u16::from_le_bytes([self.buf[0], self.buf[1]]);
+                self.len = 0;
+                if !(4..=MAX_ACK_LENGTH as u16).contains(&length) {
+                    self.state = State::Seek { data: 0, ack: 0 };
+                    return Some(Err(ProtocolError::InvalidLength { actual: length }.into()));
+                }
+                self.state = State::AckBody {
+                    length: length as usize,
+                };
+                None
+            }
+            State::AckBody { length } => {
+                self.buffer(byte);
+                if self.bytes_needed() > 0 {
+                    return None;
+                }
+                self.state = State::Seek { data: 0, ack: 0 };
+                if self.buf[length..self.len] != RADAR_ACK_EOF {
+                    return Some(Err(ProtocolError::BadTrailer.into()));
+                }
+                let mut data = heapless::Vec::new();
+                for &byte in &self.buf[4..length] {
+                    // Safety: the length was checked against the capacity
+                    unsafe { data.push_unchecked(byte) };
+                }
+                Some(Ok(Event::Ack(Ack {
+                    command: u16::from_le_bytes([self.buf[0], self.buf[1]]),
+                    status: u16::from_le_bytes([self.buf[2], self.buf[3]]),
+                    data,
+                })))
+            }
+        }
+    }
+
+    fn buffer(&mut self, byte: u8) {
+        self.buf[self.len] = byte;
+        self.len += 1;
+    }
+}
+
+/// An iterator over the frames decoded from a slice of bytes
+pub struct Events<'a> {
+    decoder: &'a mut Decoder,
+    data: &'a [u8],
+}
+
+impl Iterator for Events<'_> {
+    type Item = Result<Event, RadarError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((&byte, rest)) = self.data.split_first() {
+            self.data = rest;
+            if let Some(event) = self.decoder.push(byte) {
+                return Some(event);
+            }
+        }
+        None
+    }
+}
+
+/// Advances the header search by one byte, returning the new number of matched bytes
+fn seek(header: &[u8; 4], matched: usize, byte: u8) -> usize {
+    if header[matched] == byte {
+        matched + 1
+    } else if header[0] == byte {
+        // reset the search, potentially catching the new start
+        1
+    } else {
+        0
+    }
+}
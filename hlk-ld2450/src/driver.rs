@@ -0,0 +1,745 @@
+//! The radar driver, written once as async code.
+//!
+//! With the `blocking` feature, a blocking copy of the driver for `embedded-io` serial
+//! ports is generated in `blocking`, by removing every `async` and `.await`.
+
+#[maybe_async_cfg::maybe(
+    idents(
+        asynch(sync = "blocking", async = "asynch"),
+        embedded_io_async(sync = "embedded_io", async = "embedded_io_async"),
+        embedded_hal_async(sync = "embedded_hal", async = "embedded_hal_async"),
+        AsyncFn(sync = "Fn", async = "AsyncFn"),
+    ),
+    sync(
+        feature = "blocking",
+        outer(
+            doc = "The driver for blocking `embedded-io` serial ports.",
+            doc = "",
+            doc = "Its API is the same as the async [`LD2450`](crate::LD2450), without `.await`."
+        )
+    ),
+    async()
+)]
+// Failed mode switches hand the driver back along with the error
+#[allow(clippy::result_large_err, clippy::type_complexity)]
+pub mod asynch {
+    use core::marker::PhantomData;
+
+    use crate::{
+        config::{BluetoothPassword, FilteringMode, TargetTrackingMode},
+        decoder::{self, Ack, Decoder, Event},
+        frame::Clock,
+        retry::{NoDelay, RetryPolicy, RetryStats},
+        BaudRate, Config, ConfigError, ConfigurationMode, Desync, FirmwareVersion, Frame,
+        MacAddress, NormalMode, ProtocolError, RadarError, RadarTarget, SetBaudRate,
+        ACK_SEEK_LIMIT, RESYNC_DATA_FRAMES, STARTUP_SEEK_LIMIT,
+    };
+    #[maybe_async_cfg::only_if(async)]
+    use crate::{timeout, RESTART_TIMEOUT_MS};
+    // The braces let the commands be renamed along with the driver
+    use crate::config_writer::asynch::{self as config_writer, command};
+    use embedded_hal_async::delay::DelayNs;
+    use embedded_io_async::{Error as _, ErrorKind, Read, ReadExactError, Write};
+
+    /// The driver after a failed mode switch, along with the error that caused it
+    pub type DesyncError<Serial, Delay = NoDelay> = (LD2450<Serial, Desync, Delay>, RadarError);
+
+    #[derive(Debug, Clone, PartialEq)]
+    /// Driver for the LD2450 radar module
+    ///
+    /// The `Mode` tracks whether the radar is reporting targets ([`NormalMode`]), or
+    /// accepting configuration commands ([`ConfigurationMode`]). The `Delay` is used
+    /// to back off between retries of configuration commands, see [`LD2450::with_delay`].
+    pub struct LD2450<Serial, Mode = NormalMode, Delay = NoDelay> {
+        serial: Serial,
+        decoder: Decoder,
+        /// Waits between retries of configuration commands
+        delay: Delay,
+        retry_policy: RetryPolicy,
+        retry_stats: RetryStats,
+        /// The sequence number of the next frame
+        sequence: u32,
+        /// Timestamps data frames
+        clock: Option<Clock>,
+        mode: PhantomData<Mode>,
+    }
+
+    impl<Serial: Read> LD2450<Serial> {
+        /// Initializes the radar. Whatever configuration was previously
+        /// set will be reused.
+        ///
+        /// This method of initialization is not recommended because settings
+        /// are persisted even across power cycles of the radar, which can lead
+        /// to unexpected behavior. However, it can be useful for testing and
+        /// is available without providing a Write pin.
+        pub fn new_recycled_config(serial: Serial) -> Self {
+            Self {
+                serial,
+                decoder: Decoder::new(),
+                delay: NoDelay,
+                retry_policy: RetryPolicy::NONE,
+                retry_stats: RetryStats::default(),
+                sequence: 0,
+                clock: None,
+                mode: PhantomData,
+            }
+        }
+    }
+
+    impl<Serial: Read, Delay> LD2450<Serial, NormalMode, Delay> {
+        /// Reads the radar tracking data
+        pub async fn next_radar_targets(
+            &mut self,
+        ) -> Result<heapless::Vec<RadarTarget, 3>, RadarError> {
+            let frame = self.next_frame().await?;
+            Ok(frame.targets.into_iter().flatten().collect())
+        }
+
+        /// Reads the next data frame, keeping the slot of each target
+        pub async fn next_frame(&mut self) -> Result<Frame, RadarError> {
+            let mut budget = usize::MAX;
+            loop {
+                let event = self.next_event(&mut budget).await.map_err(|e| match e {
+                    ReadExactError::Other(e) if e.kind() == ErrorKind::TimedOut => {
+                        RadarError::Timeout
+                    }
+                    e => {
+                        log::error!("{:?}", e);
+                        RadarError::SerialError
+                    }
+                })?;
+                match event? {
+                    Event::Targets(targets) => {
+                        let frame = Frame {
+                            targets,
+                            sequence: self.sequence,
+                            timestamp: self.clock.map(|Clock(now)| now()),
+                        };
+                        self.sequence = self.sequence.wrapping_add(1);
+                        return Ok(frame);
+                    }
+                    Event::Ack(ack) => log::warn!("Discarding unexpected ACK: {:?}", ack),
+                }
+            }
+        }
+    }
+
+    impl<Serial: Read + Write> LD2450<Serial> {
+        /// Initialize the radar with a given serial port and configuration.
+        /// This is the preferred method of initialization.
+        ///
+        /// If any setting can't be applied, the serial port is returned along with the
+        /// setting that failed.
+        ///
+        /// The Bluetooth setting only takes effect after a restart, so if it is set, the radar
+        /// is restarted instead of just ending the configuration session. As with
+        /// [`LD2450::reboot`], this waits for the first data frame once the radar is back up.
+        pub async fn new(serial: Serial, config: Config) -> Result<Self, (Serial, ConfigError)> {
+            let mut session = Self::new_recycled_config(serial)
+                .enter_config()
+                .await
+                .map_err(|(radar, e)| (radar.serial, ConfigError::ModeSwitch(e)))?;
+            let result = session.write_config(&config).await;
+            let radar = if result.is_ok() && config.bluetooth_enabled.is_some() {
+                session
+                    .reboot()
+                    .await
+                    .map_err(|(radar, e)| (radar.serial, ConfigError::Restart(e)))?
+            } else {
+                session
+                    .exit_config()
+                    .await
+                    .map_err(|(radar, e)| (radar.serial, ConfigError::ModeSwitch(e)))?
+            };
+            match result {
+                Ok(()) => Ok(radar),
+                Err(e) => Err((radar.serial, e)),
+            }
+        }
+    }
+
+    impl<Serial: Read + Write, Delay: DelayNs> LD2450<Serial, NormalMode, Delay> {
+        /// Switches the radar into configuration mode. The radar stops reporting
+        /// targets until the session is ended with [`LD2450::exit_config`].
+        pub async fn enter_config(
+            mut self,
+        ) -> Result<LD2450<Serial, ConfigurationMode, Delay>, DesyncError<Serial, Delay>> {
+            match config_writer::enter_config_mode(&mut self).await {
+                Ok(()) => Ok(self.into_mode()),
+                Err(e) => Err((self.into_mode(), e.into())),
+            }
+        }
+    }
+
+    impl<Serial: Read + Write, Delay: DelayNs> LD2450<Serial, ConfigurationMode, Delay> {
+        /// Ends the configuration session, and the radar resumes reporting targets
+        pub async fn exit_config(
+            mut self,
+        ) -> Result<LD2450<Serial, NormalMode, Delay>, DesyncError<Serial, Delay>> {
+            match config_writer::exit_config_mode(&mut self).await {
+                Ok(()) => Ok(self.into_mode()),
+                Err(e) => Err((self.into_mode(), e.into())),
+            }
+        }
+
+        /// Sends a configuration command with `arg`, retrying it according to the retry policy.
+        /// Commands without arguments are sent with `()`.
+        ///
+        /// `command` is the command word sent by `send`, which the attempts are counted under.
+        async fn retry_with<A: Copy, T>(
+            &mut self,
+            command: u16,
+            arg: A,
+            send: impl AsyncFn(&mut Self, A) -> Result<T, ProtocolError>,
+        ) -> Result<T, RadarError> {
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+                let result = send(self, arg).await;
+                if let Some(result) = self.attempted(command, attempts, result).await {
+                    return result;
+                }
+            }
+        }
+
+        /// Records the outcome of an attempt, returning it if the command is done.
+        /// Otherwise, backs off and prepares the radar for the next attempt.
+        async fn attempted<T>(
+            &mut self,
+            command: u16,
+            attempts: u8,
+            result: Result<T, ProtocolError>,
+        ) -> Option<Result<T, RadarError>> {
+            let policy = self.retry_policy;
+            let error = match result {
+                Ok(value) => {
+                    self.retry_stats.record(command, attempts, true);
+                    return Some(Ok(value));
+                }
+                Err(e) => e,
+            };
+            // The radar would reject the command again
+            let rejected = matches!(error, ProtocolError::CommandFailed { .. });
+            if rejected || attempts >= policy.attempts {
+                self.retry_stats.record(command, attempts, false);
+                return Some(Err(error.into()));
+            }
+
+            log::warn!("Retrying command {:#04x} after {:?}", command, error);
+            self.delay.delay_ms(policy.backoff_ms(attempts)).await;
+            self.decoder.reset();
+            if policy.reenter_config {
+                if let Err(e) = config_writer::enter_config_mode(self).await {
+                    log::warn!("Failed to re-enter configuration mode: {:?}", e);
+                }
+            }
+            None
+        }
+
+        async fn write_config(&mut self, config: &Config) -> Result<(), ConfigError> {
+            self.set_tracking_mode(config.tracking)
+                .await
+                .map_err(ConfigError::TrackingMode)?;
+            if let Some(enabled) = config.bluetooth_enabled {
+                self.set_bluetooth_enabled(enabled)
+                    .await
+                    .map_err(ConfigError::Bluetooth)?;
+            }
+            self.set_zone_filtering(&config.filtering_mode)
+                .await
+                .map_err(ConfigError::ZoneFiltering)
+        }
+
+        /// Restarts the radar, which ends the configuration session, and waits for
+        /// the first data frame once it is back up.
+        ///
+        /// Any pending baud rate change also takes effect, in which case no data frame
+        /// will be received. See [`LD2450::change_baud_rate`] instead.
+        ///
+        /// If no data frame arrives within [`RESTART_TIMEOUT_MS`](crate::RESTART_TIMEOUT_MS), as
+        /// measured by the driver's delay, this fails with [`RadarError::NoDataFrames`]. Without
+        /// a delay, and in the blocking driver, a silent radar is waited for as long as the serial
+        /// port waits, unless it ends reads that don't complete in time.
+        pub async fn reboot(
+            mut self,
+        ) -> Result<LD2450<Serial, NormalMode, Delay>, DesyncError<Serial, Delay>> {
+            if let Err(e) = config_writer::restart(&mut self).await {
+                return Err((self.into_mode(), e.into()));
+            }
+            match self.wait_for_targets().await {
+                Ok(()) => Ok(self.into_mode()),
+                Err(e) => Err((self.into_mode(), e)),
+            }
+        }
+
+        /// Resets all settings to their factory defaults and restarts the radar, which
+        /// ends the configuration session.
+        ///
+        /// This also resets the baud rate to [`BaudRate::Baud256000`], which `host` switches
+        /// the serial port to before waiting for the first data frame. The new baud rate
+        /// is returned along with the driver. The wait is bounded as with [`LD2450::reboot`].
+        pub async fn factory_reset<H: SetBaudRate<Serial>>(
+            mut self,
+            host: &mut H,
+        ) -> Result<(LD2450<Serial, NormalMode, Delay>, BaudRate), DesyncError<Serial, Delay>>
+        {
+            if let Err(e) = config_writer::factory_restore(&mut self).await {
+                return Err((self.into_mode(), e.into()));
+            }
+            // Restored settings only apply after a restart
+            if let Err(e) = config_writer::restart(&mut self).await {
+                return Err((self.into_mode(), e.into()));
+            }
+            let baud_rate = BaudRate::default();
+            match self.switch_baud_rate(host, baud_rate).await {
+                Ok(()) => Ok((self.into_mode(), baud_rate)),
+                Err(e) => Err((self.into_mode(), e)),
+            }
+        }
+
+        /// Queries the radar firmware version
+        pub async fn firmware_version(&mut self) -> Result<FirmwareVersion, RadarError> {
+            self.retry_with(
+                command::GET_FIRMWARE_VERSION,
+                (),
+                config_writer::get_firmware_version,
+            )
+            .await
+        }
+
+        /// Queries the Bluetooth MAC address of the radar, which is unique to each module
+        pub async fn mac_address(&mut self) -> Result<MacAddress, RadarError> {
+            self.retry_with(command::GET_MAC_ADDRESS, (), config_writer::get_mac_address)
+                .await
+        }
+
+        /// Reads back the settings currently stored on the radar, which may have been
+        /// changed since they were applied, e.g. through the HLKRadarTool app.
+        ///
+        /// The radar can't report whether Bluetooth is enabled, so `bluetooth_enabled` is always `None`.
+        pub async fn read_config(&mut self) -> Result<Config, RadarError> {
+            let tracking = self.tracking_mode().await?;
+            let filtering_mode = self.zone_filtering().await?;
+            Ok(Config {
+                tracking,
+                bluetooth_enabled: None,
+                filtering_mode,
+            })
+        }
+
+        /// Queries whether the radar is tracking a single or multiple targets
+        pub async fn tracking_mode(&mut self) -> Result<TargetTrackingMode, RadarError> {
+            self.retry_with(
+                command::GET_TRACKING_MODE,
+                (),
+                config_writer::get_target_tracking_mode,
+            )
+            .await
+        }
+
+        pub async fn set_tracking_mode(
+            &mut self,
+            mode: TargetTrackingMode,
+        ) -> Result<(), RadarError> {
+            let command = config_writer::tracking_mode_command(mode);
+            self.retry_with(command, mode, config_writer::set_target_tracking_mode)
+                .await
+        }
+
+        /// Enables or disables Bluetooth. This only takes effect after the radar restarts.
+        pub async fn set_bluetooth_enabled(&mut self, enabled: bool) -> Result<(), RadarError> {
+            self.retry_with(
+                command::SET_BLUETOOTH,
+                enabled,
+                config_writer::set_bluetooth_enabled,
+            )
+            .await
+        }
+
+        /// Sets the password required to connect to the radar over Bluetooth
+        pub async fn set_bluetooth_password(
+            &mut self,
+            password: &BluetoothPassword,
+        ) -> Result<(), RadarError> {
+            self.retry_with(
+                command::SET_BLUETOOTH_PASSWORD,
+                password,
+                config_writer::set_bluetooth_password,
+            )
+            .await
+        }
+
+        /// Checks `password` against the radar's Bluetooth password, returning whether
+        /// the radar granted permission
+        pub async fn bluetooth_permission(
+            &mut self,
+            password: &BluetoothPassword,
+        ) -> Result<bool, RadarError> {
+            self.retry_with(
+                command::GET_BLUETOOTH_PERMISSION,
+                password,
+                config_writer::get_bluetooth_permission,
+            )
+            .await
+        }
+
+        /// Sets the serial baud rate of the radar. The new baud rate is persisted,
+        /// but only takes effect after the radar is rebooted.
+        ///
+        /// See [`LD2450::change_baud_rate`] to also switch the host serial port over.
+        pub async fn set_serial_baud_rate(
+            &mut self,
+            baud_rate: BaudRate,
+        ) -> Result<(), RadarError> {
+            self.retry_with(
+                command::SET_BAUD_RATE,
+                baud_rate,
+                config_writer::set_baud_rate,
+            )
+            .await
+        }
+
+        /// Changes the baud rate of both the radar and the host serial port, which ends
+        /// the configuration session.
+        ///
+        /// `host` is first checked by switching the serial port to `baud_rate` and back to
+        /// `current`, the rate the radar is communicating at. If it can't, nothing is sent.
+        ///
+        /// The new baud rate is then stored and the radar is restarted to apply it. `host`
+        /// switches the serial port over, and the change is confirmed by receiving a data frame.
+        /// If none arrives, `host` switches back to `current`, in case the radar kept it.
+        /// This only reverts the host: the radar's stored baud rate is never restored, so if
+        /// the radar did switch, it has to be found with [`LD2450::detect_baud_rate`].
+        /// The wait is bounded as with [`LD2450::reboot`].
+        pub async fn change_baud_rate<H: SetBaudRate<Serial>>(
+            mut self,
+            current: BaudRate,
+            baud_rate: BaudRate,
+            host: &mut H,
+        ) -> Result<LD2450<Serial, NormalMode, Delay>, DesyncError<Serial, Delay>> {
+            // The radar would be unreachable at a rate the host doesn't support
+            let supported = host.set_baud_rate(&mut self.serial, baud_rate).is_ok();
+            if host.set_baud_rate(&mut self.serial, current).is_err() || !supported {
+                return Err((self.into_mode(), RadarError::SerialError));
+            }
+            if let Err(e) = self.set_serial_baud_rate(baud_rate).await {
+                return Err((self.into_mode(), e));
+            }
+            if let Err(e) = config_writer::restart(&mut self).await {
+                return Err((self.into_mode(), e.into()));
+            }
+
+            let error = match self.switch_baud_rate(host, baud_rate).await {
+                Ok(()) => return Ok(self.into_mode()),
+                Err(e) => e,
+            };
+            log::warn!("No data frames at {:?}, rolling back", baud_rate);
+            match self.switch_baud_rate(host, current).await {
+                Ok(()) => log::warn!("The radar is still using {:?}", current),
+                Err(e) => log::error!("No data frames at {:?} either: {:?}", current, e),
+            }
+            Err((self.into_mode(), error))
+        }
+
+        /// Queries the regions the radar filters targets by
+        pub async fn zone_filtering(&mut self) -> Result<FilteringMode, RadarError> {
+            self.retry_with(
+                command::GET_ZONE_FILTERING,
+                (),
+                config_writer::get_zone_filtering,
+            )
+            .await
+        }
+
+        /// Replaces the regions the radar filters targets by
+        pub async fn set_zone_filtering(&mut self, mode: &FilteringMode) -> Result<(), RadarError> {
+            self.retry_with(
+                command::SET_ZONE_FILTERING,
+                mode,
+                config_writer::set_zone_filtering,
+            )
+            .await
+        }
+    }
+
+    /// The radar in the mode it was found in by [`LD2450::resync`]
+    #[derive(Debug, Clone, PartialEq)]
+    pub enum Resynced<Serial, Delay = NoDelay> {
+        Normal(LD2450<Serial, NormalMode, Delay>),
+        Configuration(LD2450<Serial, ConfigurationMode, Delay>),
+    }
+
+    impl<Serial: Read + Write> LD2450<Serial, Desync> {
+        /// Finds the baud rate the radar is communicating at, which persists across power cycles.
+        ///
+        /// `host` switches the serial port through every [`BaudRate`], starting with the default,
+        /// until the radar is found in either mode as by [`LD2450::resync`]. A radar in
+        /// configuration mode is silent at any other baud rate, so the serial port should end
+        /// reads that don't complete in time with an error, e.g. using an [`IdleTimeoutSerial`](crate::timeout::IdleTimeoutSerial).
+        /// Baud rates the host doesn't support are skipped.
+        pub async fn detect_baud_rate<H: SetBaudRate<Serial>>(
+            serial: Serial,
+            host: &mut H,
+        ) -> Result<(Resynced<Serial>, BaudRate), (Serial, RadarError)> {
+            let mut radar: Self = LD2450::new_recycled_config(serial).into_mode();
+            let others = BaudRate::ALL
+                .into_iter()
+                .filter(|&baud_rate| baud_rate != BaudRate::default());
+            for baud_rate in core::iter::once(BaudRate::default()).chain(others) {
+                if host.set_baud_rate(&mut radar.serial, baud_rate).is_err() {
+                    log::warn!("The host does not support {:?}", baud_rate);
+                    continue;
+                }
+                match radar.probe_config_mode().await {
+                    Ok(true) => return Ok((Resynced::Configuration(radar.into_mode()), baud_rate)),
+                    Ok(false) => return Ok((Resynced::Normal(radar.into_mode()), baud_rate)),
+                    Err(e) => log::debug!("No response at {:?}: {:?}", baud_rate, e),
+                }
+            }
+            Err((radar.serial, RadarError::Desyncronized))
+        }
+    }
+
+    impl<Serial: Read + Write, Delay: DelayNs> LD2450<Serial, Desync, Delay> {
+        /// Determines which mode the radar is in, without changing it.
+        ///
+        /// Any partially received frame is discarded, then the radar is sent a query that it
+        /// only acknowledges in configuration mode. In normal mode the query is ignored, and
+        /// the radar is assumed to be in normal mode once a few data frames arrive without an ACK.
+        pub async fn resync(
+            mut self,
+        ) -> Result<Resynced<Serial, Delay>, DesyncError<Serial, Delay>> {
+            match self.probe_config_mode().await {
+                Ok(true) => Ok(Resynced::Configuration(self.into_mode())),
+                Ok(false) => Ok(Resynced::Normal(self.into_mode())),
+                Err(e) => Err((self, e)),
+            }
+        }
+
+        /// Brings the radar back to normal mode, ending the configuration session
+        /// it may have been left in.
+        pub async fn recover(
+            self,
+        ) -> Result<LD2450<Serial, NormalMode, Delay>, DesyncError<Serial, Delay>> {
+            match self.resync().await? {
+                Resynced::Normal(radar) => Ok(radar),
+                Resynced::Configuration(radar) => radar.exit_config().await,
+            }
+        }
+
+        /// Brings the radar into configuration mode, re-entering it if the radar had already left.
+        pub async fn recover_config(
+            self,
+        ) -> Result<LD2450<Serial, ConfigurationMode, Delay>, DesyncError<Serial, Delay>> {
+            match self.resync().await? {
+                Resynced::Normal(radar) => radar.enter_config().await,
+                Resynced::Configuration(radar) => Ok(radar),
+            }
+        }
+    }
+
+    impl<Serial: Read, Mode, Delay> LD2450<Serial, Mode, Delay> {
+        /// Reads from the serial port until the decoder completes a frame.
+        ///
+        /// Gives up with [`ProtocolError::BadHeader`] once `budget` bytes have been read.
+        async fn next_event(
+            &mut self,
+            budget: &mut usize,
+        ) -> Result<Result<Event, RadarError>, ReadExactError<Serial::Error>> {
+            read_event(&mut self.serial, &mut self.decoder, budget).await
+        }
+    }
+
+    impl<Serial: Read, Mode, Delay: DelayNs> LD2450<Serial, Mode, Delay> {
+        /// Switches the host serial port to `baud_rate`, and waits for a data frame to arrive
+        async fn switch_baud_rate<H: SetBaudRate<Serial>>(
+            &mut self,
+            host: &mut H,
+            baud_rate: BaudRate,
+        ) -> Result<(), RadarError> {
+            host.set_baud_rate(&mut self.serial, baud_rate)
+                .map_err(|_| RadarError::SerialError)?;
+            self.wait_for_targets().await
+        }
+
+        /// Discards any partial frame, and waits for the radar to report targets.
+        ///
+        /// Fails with [`RadarError::NoDataFrames`] if only garbage arrives, or if nothing
+        /// arrives within [`RESTART_TIMEOUT_MS`]. A delay that doesn't wait, like the default
+        /// [`NoDelay`], can't measure the timeout, so then the wait is as long as the serial port's.
+        #[maybe_async_cfg::only_if(async)]
+        async fn wait_for_targets(&mut self) -> Result<(), RadarError> {
+            self.decoder.reset();
+            let seek = seek_targets(&mut self.serial, &mut self.decoder);
+            let timeout = timeout::measured(self.delay.delay_ms(RESTART_TIMEOUT_MS));
+            timeout::with_deadline(seek, timeout)
+                .await
+                .unwrap_or(Err(RadarError::NoDataFrames))
+        }
+
+        /// Discards any partial frame, and waits for the radar to report targets.
+        ///
+        /// Fails with [`RadarError::NoDataFrames`] if only garbage arrives. Blocking reads can't
+        /// be interrupted, so if nothing arrives at all, the wait is as long as the serial port's.
+        #[maybe_async_cfg::only_if(sync)]
+        fn wait_for_targets(&mut self) -> Result<(), RadarError> {
+            self.decoder.reset();
+            seek_targets(&mut self.serial, &mut self.decoder)
+        }
+    }
+
+    /// Reads from `serial` until `decoder` completes a frame.
+    ///
+    /// Gives up with [`ProtocolError::BadHeader`] once `budget` bytes have been read.
+    async fn read_event<Serial: Read>(
+        serial: &mut Serial,
+        decoder: &mut Decoder,
+        budget: &mut usize,
+    ) -> Result<Result<Event, RadarError>, ReadExactError<Serial::Error>> {
+        let mut buf = [0; decoder::MAX_FRAME_BODY];
+        loop {
+            if *budget == 0 {
+                return Ok(Err(ProtocolError::BadHeader.into()));
+            }
+            // Never read past the end of the current frame, so nothing has to be
+            // buffered between calls
+            let len = decoder.bytes_needed().min(*budget);
+            let read = serial
+                .read(&mut buf[..len])
+                .await
+                .map_err(ReadExactError::Other)?;
+            if read == 0 {
+                return Err(ReadExactError::UnexpectedEof);
+            }
+            *budget -= read;
+
+            for &byte in &buf[..read] {
+                if let Some(event) = decoder.push(byte) {
+                    return Ok(event);
+                }
+            }
+        }
+    }
+
+    /// Reads until the first data frame, skipping up to [`STARTUP_SEEK_LIMIT`] bytes of garbage
+    async fn seek_targets<Serial: Read>(
+        serial: &mut Serial,
+        decoder: &mut Decoder,
+    ) -> Result<(), RadarError> {
+        let mut budget = STARTUP_SEEK_LIMIT;
+        loop {
+            match read_event(serial, decoder, &mut budget).await {
+                Ok(Ok(Event::Targets(_))) => return Ok(()),
+                // garbage may decode as a corrupted frame
+                Ok(_) if budget > 0 => {}
+                Ok(_) => return Err(RadarError::NoDataFrames),
+                Err(e) => return Err(ProtocolError::from(e).into()),
+            }
+        }
+    }
+
+    impl<Serial: Read + Write, Mode, Delay> LD2450<Serial, Mode, Delay> {
+        /// Discards any partial frame and queries the radar, returning whether it responded
+        /// in configuration mode, or kept reporting targets in normal mode
+        async fn probe_config_mode(&mut self) -> Result<bool, RadarError> {
+            self.decoder.reset();
+            config_writer::write_probe(self).await?;
+
+            let mut budget = ACK_SEEK_LIMIT;
+            let mut frames = 0;
+            loop {
+                match self.next_event(&mut budget).await {
+                    Ok(Ok(Event::Ack(ack))) if config_writer::is_probe_ack(&ack) => {
+                        return Ok(true)
+                    }
+                    Ok(Ok(Event::Targets(_))) => {
+                        // Frames received before the query was sent may still be buffered
+                        frames += 1;
+                        if frames == RESYNC_DATA_FRAMES {
+                            return Ok(false);
+                        }
+                    }
+                    // ACKs for earlier commands, or corrupted frames
+                    Ok(_) if budget > 0 => {}
+                    Ok(_) => return Err(RadarError::Desyncronized),
+                    Err(e) => return Err(ProtocolError::from(e).into()),
+                }
+            }
+        }
+    }
+
+    impl<Serial: Read + Write, Mode, Delay> config_writer::Transport for LD2450<Serial, Mode, Delay> {
+        async fn write_all(&mut self, data: &[u8]) -> Result<(), ProtocolError> {
+            self.serial
+                .write_all(data)
+                .await
+                .map_err(|e| ProtocolError::Io(e.kind()))
+        }
+
+        async fn read_ack(&mut self, command: u16) -> Result<Ack, ProtocolError> {
+            let mut budget = ACK_SEEK_LIMIT;
+            loop {
+                match self.next_event(&mut budget).await? {
+                    Ok(Event::Ack(ack)) if config_writer::acknowledges(&ack, command) => {
+                        return Ok(ack)
+                    }
+                    Ok(Event::Ack(ack)) => log::warn!("Discarding stale ACK: {:?}", ack),
+                    Ok(Event::Targets(_)) => {}
+                    Err(RadarError::Protocol(e)) => return Err(e),
+                    // corrupted data frames are of no interest here
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+
+    impl<Serial, Mode, Delay> LD2450<Serial, Mode, Delay> {
+        /// Uses `delay` to wait between retries of configuration commands
+        pub fn with_delay<D: DelayNs>(self, delay: D) -> LD2450<Serial, Mode, D> {
+            LD2450 {
+                serial: self.serial,
+                decoder: self.decoder,
+                delay,
+                retry_policy: self.retry_policy,
+                retry_stats: self.retry_stats,
+                sequence: self.sequence,
+                clock: self.clock,
+                mode: PhantomData,
+            }
+        }
+
+        /// Sets how configuration commands are retried when their ACK is lost.
+        /// By default, commands are not retried.
+        pub fn set_retry_policy(&mut self, policy: RetryPolicy) {
+            self.retry_policy = policy;
+        }
+
+        /// Timestamps each [`Frame`] with `now`, in any unit, e.g. microseconds since boot
+        pub fn set_clock(&mut self, now: fn() -> u64) {
+            self.clock = Some(Clock(now));
+        }
+
+        /// The attempts made for each configuration command so far
+        pub fn retry_stats(&self) -> &RetryStats {
+            &self.retry_stats
+        }
+
+        /// Consumes the driver and returns the inner serial port
+        pub fn into_inner(self) -> Serial {
+            self.serial
+        }
+
+        fn into_mode<M>(self) -> LD2450<Serial, M, Delay> {
+            LD2450 {
+                serial: self.serial,
+                decoder: self.decoder,
+                delay: self.delay,
+                retry_policy: self.retry_policy,
+                retry_stats: self.retry_stats,
+                sequence: self.sequence,
+                clock: self.clock,
+                mode: PhantomData,
+            }
+        }
+    }
+}
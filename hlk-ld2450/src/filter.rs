@@ -0,0 +1,245 @@
+//! A constant-velocity Kalman filter smoothing a target's position, and estimating its velocity.
+//!
+//! Each [`Track`](crate::tracking::Track) of a [`Tracker`](crate::tracking::Tracker) has a
+//! filter, which can also be used on its own:
+//!
+//! ```
+//! use hlk_ld2450::{filter::{FilterConfig, KalmanFilter}, RadarTarget};
+//!
+//! let config = FilterConfig::default();
+//! let mut target = RadarTarget {
+//!     x_coordinate: 0,
+//!     y_coordinate: 1000,
+//!     speed: 0,
+//!     resolution: 320,
+//! };
+//! let mut filter = KalmanFilter::new(&config, &target);
+//! for _ in 0..20 {
+//!     // walking along the x axis at 50 cm/s, at 10 frames per second
+//!     target.x_coordinate += 50;
+//!     filter.predict(&config, 0.1);
+//!     filter.update(&config, &target);
+//! }
+//! assert!((filter.velocity().x.0 - 50.0).abs() < 5.0);
+//! ```
+
+use crate::{
+    units::{CentimetersPerSecond, Millimeters, Position, Velocity},
+    RadarTarget,
+};
+
+/// The state is [x, y, vx, vy], in mm and mm/s
+type Vector = [f32; 4];
+type Matrix = [[f32; 4]; 4];
+
+/// The initial uncertainty of the velocity, as the first measurement has none
+const INITIAL_SPEED_STD_DEV: f32 = 1000.0;
+
+/// How much the filter trusts its model and the measurements, as standard deviations
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FilterConfig {
+    /// How quickly targets change velocity, in mm/s². Larger values follow turns
+    /// more closely, smaller values smooth more.
+    pub acceleration_noise: f32,
+    /// The jitter of the reported coordinates
+    pub position_noise: Millimeters,
+    /// The jitter of the reported [`RadarTarget::speed`], or `None` to ignore it.
+    /// The speed only measures the velocity along the line of sight.
+    pub speed_noise: Option<CentimetersPerSecond>,
+}
+
+impl Default for FilterConfig {
+    fn default() -> Self {
+        Self {
+            acceleration_noise: 1000.0,
+            position_noise: Millimeters(50.0),
+            speed_noise: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct KalmanFilter {
+    state: Vector,
+    covariance: Matrix,
+}
+
+impl KalmanFilter {
+    /// Starts at the target's position, not moving
+    pub fn new(config: &FilterConfig, target: &RadarTarget) -> Self {
+        let position_variance = config.position_noise.0 * config.position_noise.0;
+        let speed_variance = INITIAL_SPEED_STD_DEV * INITIAL_SPEED_STD_DEV;
+        let mut covariance = [[0.0; 4]; 4];
+        covariance[0][0] = position_variance;
+        covariance[1][1] = position_variance;
+        covariance[2][2] = speed_variance;
+        covariance[3][3] = speed_variance;
+        Self {
+            state: [target.x().0, target.y().0, 0.0, 0.0],
+            covariance,
+        }
+    }
+
+    /// The smoothed position
+    pub fn position(&self) -> Position {
+        Position {
+            x: Millimeters(self.state[0]),
+            y: Millimeters(self.state[1]),
+        }
+    }
+
+    /// The estimated velocity, including the part across the line of sight
+    pub fn velocity(&self) -> Velocity {
+        Velocity {
+            x: CentimetersPerSecond(self.state[2] / 10.0),
+            y: CentimetersPerSecond(self.state[3] / 10.0),
+        }
+    }
+
+    /// Moves the estimate forward by `dt` seconds
+    pub fn predict(&mut self, config: &FilterConfig, dt: f32) {
+        // x' = F x, with F adding the velocity times dt to the position
+        self.state[0] += self.state[2] * dt;
+        self.state[1] += self.state[3] * dt;
+
+        // P' = F P F^T + Q
+        let p = &mut self.covariance;
+        for row in p.iter_mut() {
+            row[0] += row[2] * dt;
+            row[1] += row[3] * dt;
+        }
+        let (positions, speeds) = p.split_at_mut(2);
+        for (position, speed) in positions.iter_mut().zip(speeds.iter()) {
+            for (value, rate) in position.iter_mut().zip(speed) {
+                *value += rate * dt;
+            }
+        }
+        // Discrete white noise acceleration, for each axis
+        let variance = config.acceleration_noise * config.acceleration_noise;
+        let (dt2, dt3, dt4) = (dt * dt, dt * dt * dt, dt * dt * dt * dt);
+        for axis in 0..2 {
+            let (position, speed) = (axis, axis + 2);
+            p[position][position] += variance * dt4 / 4.0;
+            p[position][speed] += variance * dt3 / 2.0;
+            p[speed][position] += variance * dt3 / 2.0;
+            p[speed][speed] += variance * dt2;
+        }
+    }
+
+    /// Corrects the estimate with the target's coordinates, and its speed if configured
+    pub fn update(&mut self, config: &FilterConfig, target: &RadarTarget) {
+        let position_variance = config.position_noise.0 * config.position_noise.0;
+        self.correct([1.0, 0.0, 0.0, 0.0], target.x().0, position_variance);
+        self.correct([0.0, 1.0, 0.0, 0.0], target.y().0, position_variance);
+
+        let Some(speed_noise) = config.speed_noise else {
+            return;
+        };
+        // The speed is the velocity projected on the line of sight, linearized around
+        // the current position
+        let [x, y, vx, vy] = self.state;
+        let range = libm::hypotf(x, y);
+        if range < 1.0 {
+            return;
+        }
+        let h = [0.0, 0.0, x / range, y / range];
+        let predicted = vx * h[2] + vy * h[3];
+        let measured = target.radial_speed().0 * 10.0;
+        let speed_variance = speed_noise.0 * speed_noise.0 * 100.0;
+        self.correct_innovation(h, measured - predicted, speed_variance);
+    }
+
+    /// A scalar Kalman update with the measurement `z` of `h · state`
+    fn correct(&mut self, h: Vector, z: f32, variance: f32) {
+        let predicted: f32 = h.iter().zip(self.state).map(|(h, x)| h * x).sum();
+        self.correct_innovation(h, z - predicted, variance);
+    }
+
+    fn correct_innovation(&mut self, h: Vector, innovation: f32, variance: f32) {
+        let p = &mut self.covariance;
+        // P h^T, which is also (h P)^T as P is symmetric
+        let mut ph = [0.0; 4];
+        for (i, value) in ph.iter_mut().enumerate() {
+            *value = (0..4).map(|j| p[i][j] * h[j]).sum();
+        }
+        let s: f32 = (0..4).map(|i| h[i] * ph[i]).sum::<f32>() + variance;
+        if s <= 0.0 {
+            return;
+        }
+        let gain = ph.map(|value| value / s);
+
+        for (x, k) in self.state.iter_mut().zip(gain) {
+            *x += k * innovation;
+        }
+        // P' = P - K (h P)
+        for (i, row) in p.iter_mut().enumerate() {
+            for (j, value) in row.iter_mut().enumerate() {
+                *value -= gain[i] * ph[j];
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(x_coordinate: i16, y_coordinate: i16, speed: i16) -> RadarTarget {
+        RadarTarget {
+            x_coordinate,
+            y_coordinate,
+            speed,
+            resolution: 320,
+        }
+    }
+
+    #[test]
+    fn test_smooths_jitter() {
+        let config = FilterConfig::default();
+        let mut filter = KalmanFilter::new(&config, &target(0, 2000, 0));
+        for i in 0..50 {
+            let jitter = if i % 2 == 0 { 40 } else { -40 };
+            filter.predict(&config, 0.1);
+            filter.update(&config, &target(jitter, 2000 - jitter, 0));
+        }
+        let position = filter.position();
+        assert!(position.x.0.abs() < 15.0, "{:?}", position);
+        assert!((position.y.0 - 2000.0).abs() < 15.0, "{:?}", position);
+    }
+
+    #[test]
+    fn test_fusing_speed_converges_faster() {
+        let plain = FilterConfig::default();
+        let fused = FilterConfig {
+            speed_noise: Some(CentimetersPerSecond(5.0)),
+            ..plain
+        };
+        // Walking away from the radar at 100 cm/s
+        let mut filters = [
+            KalmanFilter::new(&plain, &target(0, 1000, 100)),
+            KalmanFilter::new(&fused, &target(0, 1000, 100)),
+        ];
+        for (filter, config) in filters.iter_mut().zip([plain, fused]) {
+            for frame in 1..=2 {
+                filter.predict(&config, 0.1);
+                filter.update(&config, &target(0, 1000 + frame * 100, 100));
+            }
+        }
+        let error = |filter: &KalmanFilter| (filter.velocity().y.0 - 100.0).abs();
+        assert!(error(&filters[1]) < error(&filters[0]));
+        assert!(error(&filters[1]) < 5.0, "{:?}", filters[1].velocity());
+    }
+
+    #[test]
+    fn test_predict_coasts_along_velocity() {
+        let config = FilterConfig::default();
+        let mut filter = KalmanFilter::new(&config, &target(0, 1000, 0));
+        for frame in 1..=30 {
+            filter.predict(&config, 0.1);
+            filter.update(&config, &target(frame * 20, 1000, 0));
+        }
+        let before = filter.position().x.0;
+        filter.predict(&config, 0.5);
+        assert!((filter.position().x.0 - before - 100.0).abs() < 10.0);
+    }
+}
@@ -3,9 +3,11 @@ use core::fmt;
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 pub struct FirmwareVersion {
     /// It is unclear from the datasheet what this is used for, and when it would ever
-    /// not be 0. It is assumed by this crate that 0 corresponds to the "V1" in the version string
+    /// not be 0. It is not part of the version string.
     pub firmware_type: u16,
+    /// The major and minor version as `0xMMmm`, e.g. `0x0102` for "V1.02"
     pub major: u16,
+    /// The build, whose hex digits read as its date, e.g. `0x22062416`
     pub minor: u32,
 }
 
@@ -25,13 +27,8 @@ impl From<&[u8; 8]> for FirmwareVersion {
 
 impl fmt::Display for FirmwareVersion {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(
-            f,
-            "V{}.{:02}.{}",
-            self.firmware_type + 1,
-            self.major,
-            self.minor
-        )
+        let [major, minor] = self.major.to_be_bytes();
+        write!(f, "V{:x}.{:02x}.{:08x}", major, minor, self.minor)
     }
 }
 
@@ -112,16 +109,35 @@ mod tests {
         assert!(v1 < v3);
     }
 
+    #[test]
+    fn test_from_datasheet_ack() {
+        // The version reported in the datasheet's 0xA0 ACK
+        let data = [0x00, 0x00, 0x02, 0x01, 0x16, 0x24, 0x06, 0x22];
+        let firmware_version = FirmwareVersion::from(&data);
+        assert_eq!(
+            firmware_version,
+            FirmwareVersion {
+                firmware_type: 0,
+                major: 0x0102,
+                minor: 0x22062416,
+            }
+        );
+
+        let mut s = heapless::String::<32>::new();
+        write!(s, "{}", firmware_version).unwrap();
+        assert_eq!(s, "V1.02.22062416");
+    }
+
     #[test]
     fn test_display_version() {
         let firmware_version = FirmwareVersion {
             firmware_type: 0,
-            major: 2,
-            minor: 22062416,
+            major: 0x0202,
+            minor: 0x22121510,
         };
         let mut s = heapless::String::<32>::new();
         write!(s, "{}", firmware_version).unwrap();
 
-        assert_eq!(s, "V1.02.22062416");
+        assert_eq!(s, "V2.02.22121510");
     }
 }
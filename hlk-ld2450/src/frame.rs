@@ -0,0 +1,32 @@
+use crate::RadarTarget;
+
+/// The targets reported in one data frame, and when it was received
+#[derive(Debug, Clone, PartialEq)]
+pub struct Frame {
+    /// The radar's three target slots, `None` when a slot isn't tracking a target.
+    /// A target keeps its slot from frame to frame while the radar tracks it.
+    pub targets: [Option<RadarTarget>; 3],
+    /// Counts the frames read by the driver, starting at 0 and wrapping around
+    pub sequence: u32,
+    /// When the frame was received, if a clock was set with
+    /// [`LD2450::set_clock`](crate::LD2450::set_clock)
+    pub timestamp: Option<u64>,
+}
+
+impl Frame {
+    /// The tracked targets, skipping empty slots
+    pub fn tracked(&self) -> impl Iterator<Item = &RadarTarget> {
+        self.targets.iter().flatten()
+    }
+}
+
+/// The function timestamping frames
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Clock(pub(crate) fn() -> u64);
+
+impl PartialEq for Clock {
+    fn eq(&self, other: &Self) -> bool {
+        // The same function may have several addresses, so this is only a best effort
+        core::ptr::fn_addr_eq(self.0, other.0)
+    }
+}
@@ -1,18 +1,41 @@
 #![no_std]
 
+#[cfg(any(feature = "std", feature = "codec"))]
+extern crate std;
+
+#[cfg(feature = "std")]
+pub mod adapters;
+#[cfg(feature = "codec")]
+pub mod codec;
 pub mod config;
 mod config_writer;
+pub mod decoder;
+mod driver;
+pub mod filter;
 mod firmware_version;
+mod frame;
+mod mac_address;
+pub mod pose;
 mod radar_target;
+pub mod retry;
+#[cfg(feature = "stream")]
+mod stream;
+pub mod timeout;
+pub mod tracking;
+pub mod units;
 
-use core::{fmt, marker::PhantomData};
+use core::fmt;
 
-pub use config::Config;
+pub use config::{Config, ConfigError};
+pub use driver::asynch::{DesyncError, Resynced, LD2450};
+#[cfg(feature = "blocking")]
+pub use driver::blocking;
 pub use firmware_version::FirmwareVersion;
+pub use frame::Frame;
+pub use mac_address::MacAddress;
 pub use radar_target::RadarTarget;
 
-use embedded_io_async::{Read, Write};
-use radar_target::decode_radar_targets;
+use embedded_io_async::{ErrorKind, ReadExactError};
 
 const RADAR_DATA_HEADER: [u8; 4] = [0xAA, 0xFF, 0x03, 0x00];
 const RADAR_DATA_EOF: [u8; 2] = [0x55, 0xCC];
@@ -20,6 +43,17 @@ const RADAR_DATA_FRAME_SIZE: usize = 24;
 
 const RADAR_ACK_HEADER: [u8; 4] = [0xFD, 0xFC, 0xFB, 0xFA];
 const RADAR_ACK_EOF: [u8; 4] = [0x04, 0x03, 0x02, 0x01];
+/// The number of bytes that may be skipped while waiting for an ACK.
+/// Data frames may still arrive between sending a command and receiving its ACK.
+const ACK_SEEK_LIMIT: usize = 256;
+/// The number of data frames without an ACK after which the radar is considered to be in normal mode
+const RESYNC_DATA_FRAMES: usize = 3;
+/// The number of bytes that may be received before the first data frame after a restart.
+/// Bytes sent while the radar restarts, or at a mismatched baud rate, are garbage.
+const STARTUP_SEEK_LIMIT: usize = 256;
+/// How long the async driver waits for the first data frame after restarting the radar,
+/// as measured by its delay, see [`LD2450::with_delay`]
+pub const RESTART_TIMEOUT_MS: u32 = 5000;
 
 #[derive(Debug, Eq, PartialEq, Clone, Copy, PartialOrd, Default)]
 pub enum BaudRate {
@@ -35,6 +69,18 @@ pub enum BaudRate {
 }
 
 impl BaudRate {
+    /// Every baud rate supported by the radar, from slowest to fastest
+    pub const ALL: [BaudRate; 8] = [
+        BaudRate::Baud9600,
+        BaudRate::Baud19200,
+        BaudRate::Baud38400,
+        BaudRate::Baud57600,
+        BaudRate::Baud115200,
+        BaudRate::Baud230400,
+        BaudRate::Baud256000,
+        BaudRate::Baud460800,
+    ];
+
     fn byte_repr(&self) -> u16 {
         match self {
             BaudRate::Baud9600 => 0x01,
@@ -47,6 +93,45 @@ impl BaudRate {
             BaudRate::Baud460800 => 0x08,
         }
     }
+
+    /// The baud rate in bits per second, for configuring the host serial port
+    pub fn bits_per_second(&self) -> u32 {
+        match self {
+            BaudRate::Baud9600 => 9600,
+            BaudRate::Baud19200 => 19200,
+            BaudRate::Baud38400 => 38400,
+            BaudRate::Baud57600 => 57600,
+            BaudRate::Baud115200 => 115200,
+            BaudRate::Baud230400 => 230400,
+            BaudRate::Baud256000 => 256000,
+            BaudRate::Baud460800 => 460800,
+        }
+    }
+}
+
+/// Changes the baud rate of the host serial port connected to the radar.
+///
+/// This is implemented for closures taking the serial port and the new baud rate,
+/// for serial ports that can't implement it directly.
+pub trait SetBaudRate<Serial> {
+    type Error;
+
+    fn set_baud_rate(
+        &mut self,
+        serial: &mut Serial,
+        baud_rate: BaudRate,
+    ) -> Result<(), Self::Error>;
+}
+
+impl<Serial, E, F> SetBaudRate<Serial> for F
+where
+    F: FnMut(&mut Serial, BaudRate) -> Result<(), E>,
+{
+    type Error = E;
+
+    fn set_baud_rate(&mut self, serial: &mut Serial, baud_rate: BaudRate) -> Result<(), E> {
+        self(serial, baud_rate)
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -54,148 +139,129 @@ pub enum RadarError {
     /// This is thrown when the driver reaches the end of
     /// a frame without the corresponding EOF token
     UnexpectedFrameSize,
+    /// The serial port threw an error, or its baud rate could not be changed
     SerialError,
-    /// The radar may ave been left in the config state
-    /// due to a serial error during a state change.
-    /// This might resolve itself???  TODO: idk
+    /// The serial port stayed idle for too long, see [`timeout::IdleTimeoutSerial`]
+    Timeout,
+    /// No data frame was found in the data received from the radar after it restarted,
+    /// e.g. because it came back at another baud rate
+    NoDataFrames,
+    /// A configuration command was not acknowledged as expected
+    Protocol(ProtocolError),
+    /// The radar may have been left in the config state
+    /// due to a serial error during a state change, and it
+    /// could not be determined which mode it is in.
+    ///
+    /// See [`LD2450::resync`] for the recovery procedure.
     Desyncronized,
 }
 
-pub struct NormalMode;
-pub struct ConfigurationMode;
-/// The serial port threw errors during a state change and it is
-/// unclear whether the radar is in normal or configuration mode
-pub struct Desync;
+/// Errors encountered while waiting for the radar to acknowledge a configuration command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProtocolError {
+    /// No ACK header was found in the data received from the radar
+    BadHeader,
+    /// The ACK did not end with the expected EOF token
+    BadTrailer,
+    /// The ACK length did not match the length of the expected reply
+    LengthMismatch { expected: u16, actual: u16 },
+    /// The ACK length was too short for a command word and status, or longer than any reply
+    InvalidLength { actual: u16 },
+    /// The ACK was for a different command than the one sent
+    UnexpectedCommand { expected: u16, actual: u16 },
+    /// The radar reported that it failed to execute the command
+    CommandFailed { command: u16, status: u16 },
+    /// The ACK payload contained a value that could not be decoded
+    InvalidData,
+    /// The serial port reached the end of its data in the middle of an ACK
+    UnexpectedEof,
+    /// The serial port threw an error
+    Io(ErrorKind),
+}
 
-impl core::fmt::Debug for Desync {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str("the device encountered a serial error during mode switch")
+impl<E: embedded_io_async::Error> From<ReadExactError<E>> for ProtocolError {
+    fn from(e: ReadExactError<E>) -> Self {
+        match e {
+            ReadExactError::UnexpectedEof => ProtocolError::UnexpectedEof,
+            ReadExactError::Other(e) => ProtocolError::Io(e.kind()),
+        }
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
-/// Driver for the LD2450 radar module
-pub struct LD2450<Serial> {
-    serial: Serial,
+impl From<ProtocolError> for RadarError {
+    fn from(e: ProtocolError) -> Self {
+        match e {
+            ProtocolError::Io(ErrorKind::TimedOut) => RadarError::Timeout,
+            e => RadarError::Protocol(e),
+        }
+    }
 }
 
-impl<Serial: Read> LD2450<Serial> {
-    /// Initializes the radar. Whatever configuration was previously
-    /// set will be reused.
-    ///
-    /// This method of initialization is not recommended because settings
-    /// are persisted even across power cycles of the radar, which can lead
-    /// to unexpected behavior. However, it can be useful for testing and
-    /// is available without providing a Write pin.
-    pub fn new_recycled_config(serial: Serial) -> Self {
-        Self { serial }
+impl fmt::Display for RadarError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RadarError::UnexpectedFrameSize => f.write_str("data frame without an EOF"),
+            RadarError::SerialError => f.write_str("serial port error"),
+            RadarError::Timeout => f.write_str("the radar did not respond in time"),
+            RadarError::NoDataFrames => f.write_str("no data frames after the restart"),
+            RadarError::Protocol(_) => f.write_str("configuration command failed"),
+            RadarError::Desyncronized => f.write_str("the radar mode is unknown"),
+        }
     }
+}
 
-    /// Reads the radar tracking data
-    pub async fn next_radar_targets(
-        &mut self,
-    ) -> Result<heapless::Vec<RadarTarget, 3>, RadarError> {
-        let mut buf = [0; RADAR_DATA_FRAME_SIZE];
-
-        // seek to the start of the next frame
-        let mut i = 0;
-        while i < RADAR_DATA_HEADER.len() {
-            self.serial.read(&mut buf[i..i + 1]).await.map_err(|e| {
-                log::error!("{:?}", e);
-                RadarError::SerialError
-            })?;
-
-            if RADAR_DATA_HEADER[i] != buf[i] {
-                // reset the search, potentially catching the new start
-                if RADAR_DATA_HEADER[0] == buf[i] {
-                    i = 1;
-                } else {
-                    i = 0;
-                }
-                continue;
+impl fmt::Display for ProtocolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProtocolError::BadHeader => f.write_str("no ACK received"),
+            ProtocolError::BadTrailer => f.write_str("ACK without an EOF"),
+            ProtocolError::LengthMismatch { expected, actual } => {
+                write!(f, "ACK length {}, expected {}", actual, expected)
             }
-            i += 1;
-        }
-
-        // read the rest of the frame, overwriting the header
-        self.serial.read_exact(&mut buf).await.map_err(|e| {
-            log::error!("{:?}", e);
-            RadarError::SerialError
-        })?;
-
-        // Read the last two EOF bytes as essentially a sanity check
-        let mut throwaway = [0; 2];
-        self.serial
-            .read_exact(&mut throwaway[..])
-            .await
-            .map_err(|e| {
-                log::error!("{:?}", e);
-                RadarError::SerialError
-            })?;
-        if throwaway != RADAR_DATA_EOF {
-            return Err(RadarError::UnexpectedFrameSize);
+            ProtocolError::InvalidLength { actual } => write!(f, "invalid ACK length {}", actual),
+            ProtocolError::UnexpectedCommand { expected, actual } => {
+                write!(
+                    f,
+                    "ACK for command {:#06x}, expected {:#06x}",
+                    actual, expected
+                )
+            }
+            ProtocolError::CommandFailed { command, status } => {
+                write!(f, "command {:#04x} failed with status {}", command, status)
+            }
+            ProtocolError::InvalidData => f.write_str("invalid ACK payload"),
+            ProtocolError::UnexpectedEof => f.write_str("end of data in the middle of an ACK"),
+            ProtocolError::Io(kind) => write!(f, "serial port error: {:?}", kind),
         }
-
-        decode_radar_targets(&buf)
     }
 }
 
-impl<Serial: Read + Write> LD2450<Serial> {
-    /// Initialize the radar with a given serial port and configuration.
-    /// This is the preferred method of initialization.
-    pub async fn new(serial: Serial, config: Config) -> Self {
-        // TODO: Set configuration
-        Self { serial }
-    }
-
-    pub async fn reboot(&mut self) -> Result<(), RadarError> {
-        todo!()
-    }
-
-    /// Perform a factory reset on the radar. This will reset all settings to
-    /// their default, and reboot the radar. Beware applying changes in serial baud rate.
-    pub async fn factory_reset(mut self) -> (Serial, Result<(), RadarError>) {
-        todo!();
-
-        (self.serial, Ok(()))
-    }
-
-    pub async fn firmware_version(&mut self) -> Result<&str, RadarError> {
-        todo!()
-    }
-
-    pub async fn set_bluetooth_enabled(&mut self, enabled: bool) -> Result<(), RadarError> {
-        config_writer::enter_config_mode(&mut self.serial)
-            .await
-            .map_err(|_| RadarError::Desyncronized)?;
-        let result = config_writer::set_bluetooth_enabled(&mut self.serial, enabled).await;
-        config_writer::enter_config_mode(&mut self.serial)
-            .await
-            .map_err(|_| RadarError::Desyncronized)?;
-
-        config_writer::ack(&mut self.serial, &mut [0; 2])
-            .await
-            .map_err(|_| RadarError::Desyncronized)?;
-        result.map_err(|_| RadarError::SerialError)
+#[cfg(feature = "std")]
+impl std::error::Error for RadarError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RadarError::Protocol(e) => Some(e),
+            _ => None,
+        }
     }
+}
 
-    pub async fn set_serial_baud_rate(&mut self, baud_rate: BaudRate) -> Result<(), RadarError> {
-        let _config_byte = baud_rate.byte_repr();
-        todo!()
-    }
+#[cfg(feature = "std")]
+impl std::error::Error for ProtocolError {}
 
-    pub async fn set_zone_filtering(
-        &mut self,
-        _zone: u8,
-        _enabled: bool,
-    ) -> Result<(), RadarError> {
-        todo!()
-    }
-}
+/// The radar is reporting targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NormalMode;
+/// The radar is accepting configuration commands, and not reporting targets
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigurationMode;
+/// The serial port threw errors during a state change and it is
+/// unclear whether the radar is in normal or configuration mode
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Desync;
 
-impl<Serial> LD2450<Serial> {
-    /// Consumes the driver and returns the inner serial port
-    pub fn into_inner(self) -> Serial {
-        self.serial
+impl core::fmt::Debug for Desync {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the device encountered a serial error during mode switch")
     }
 }
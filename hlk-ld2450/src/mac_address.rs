@@ -0,0 +1,66 @@
+use core::fmt;
+
+/// The Bluetooth MAC address of the radar, which identifies each module
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct MacAddress([u8; 6]);
+
+impl MacAddress {
+    /// The address bytes, most significant first
+    pub fn octets(&self) -> [u8; 6] {
+        self.0
+    }
+}
+
+impl From<[u8; 6]> for MacAddress {
+    fn from(octets: [u8; 6]) -> Self {
+        MacAddress(octets)
+    }
+}
+
+impl fmt::Display for MacAddress {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let [a, b, c, d, e, g] = self.0;
+        write!(f, "{a:02X}:{b:02X}:{c:02X}:{d:02X}:{e:02X}:{g:02X}")
+    }
+}
+
+impl defmt::Format for MacAddress {
+    fn format(&self, f: defmt::Formatter) {
+        let [a, b, c, d, e, g] = self.0;
+        defmt::write!(
+            f,
+            "{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}:{=u8:02X}",
+            a,
+            b,
+            c,
+            d,
+            e,
+            g
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fmt::Write;
+
+    use super::*;
+
+    #[test]
+    fn test_display_mac_address() {
+        let mac_address = MacAddress([0x8F, 0x27, 0x2E, 0xB8, 0x0F, 0x65]);
+        let mut s = heapless::String::<32>::new();
+        write!(s, "{}", mac_address).unwrap();
+
+        assert_eq!(s, "8F:27:2E:B8:0F:65");
+    }
+
+    #[test]
+    fn test_display_leading_zeros() {
+        let mac_address = MacAddress([0x00, 0x01, 0x02, 0x0A, 0x0B, 0xFF]);
+        let mut s = heapless::String::<32>::new();
+        write!(s, "{}", mac_address).unwrap();
+
+        assert_eq!(s, "00:01:02:0A:0B:FF");
+    }
+}
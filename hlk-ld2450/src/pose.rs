@@ -0,0 +1,171 @@
+//! Converting sensor-relative targets into a room coordinate frame shared by several radars.
+//!
+//! ```
+//! use hlk_ld2450::{pose::MountingPose, units::Millimeters, RadarTarget};
+//!
+//! // In the corner at (0, 0), facing diagonally into the room
+//! let pose = MountingPose {
+//!     yaw_degrees: 45.0,
+//!     ..MountingPose::default()
+//! };
+//! let target = RadarTarget {
+//!     x_coordinate: 0,
+//!     y_coordinate: 1414,
+//!     speed: 0,
+//!     resolution: 320,
+//! };
+//! let position = pose.to_room(&target);
+//! assert!((position.x.0 - 1000.0).abs() < 1.0);
+//! assert!((position.y.0 - 1000.0).abs() < 1.0);
+//! ```
+
+use crate::{
+    units::{CentimetersPerSecond, Millimeters, Position, Velocity},
+    RadarTarget,
+};
+
+/// Where a radar is mounted in the room, and which way it faces
+///
+/// The radar's y axis (its normal) points away from the sensor, and its x axis to the
+/// side. The default pose is at the room origin, with the axes of the room.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MountingPose {
+    /// The position of the radar in the room
+    pub position: Position,
+    /// The angle from the room's y axis to the radar's normal, positive towards the
+    /// room's x axis, like [`RadarTarget::azimuth_degrees`]
+    pub yaw_degrees: f32,
+    /// How far the radar is mounted above the targets, e.g. above chest height.
+    /// The radar reports slant ranges, which are corrected to ground distances.
+    pub height: Option<Millimeters>,
+    /// How far the radar's normal is tilted down from horizontal. Targets are projected
+    /// from the tilted plane onto the ground, and then moved to the distance given by the
+    /// `height`, if any.
+    pub tilt_degrees: f32,
+}
+
+impl MountingPose {
+    /// Converts the target's position into room coordinates
+    pub fn to_room(&self, target: &RadarTarget) -> Position {
+        self.position_to_room(target.position())
+    }
+
+    /// Converts a position relative to the radar into room coordinates
+    pub fn position_to_room(&self, position: Position) -> Position {
+        let ground = self.ground_position(position);
+        let rotated = self.rotate(ground.x.0, ground.y.0);
+        Position {
+            x: self.position.x + Millimeters(rotated.0),
+            y: self.position.y + Millimeters(rotated.1),
+        }
+    }
+
+    /// Rotates a velocity relative to the radar into room coordinates.
+    /// The slant of tilted installs is not corrected.
+    pub fn velocity_to_room(&self, velocity: Velocity) -> Velocity {
+        let (x, y) = self.rotate(velocity.x.0, velocity.y.0);
+        Velocity {
+            x: CentimetersPerSecond(x),
+            y: CentimetersPerSecond(y),
+        }
+    }
+
+    /// The position on the ground, relative to the point below the radar
+    fn ground_position(&self, position: Position) -> Position {
+        // Projects the tilted plane onto the ground, which keeps the x axis
+        let x = position.x.0;
+        let y = position.y.0 * libm::cosf(self.tilt_degrees.to_radians());
+        let Some(height) = self.height else {
+            return Position {
+                x: Millimeters(x),
+                y: Millimeters(y),
+            };
+        };
+        let projected = libm::hypotf(x, y);
+        if projected < 1.0 {
+            return Position::default();
+        }
+        // The slant range is the hypotenuse of the height and the ground distance.
+        // Targets closer than the height are below the radar.
+        let range = libm::hypotf(position.x.0, position.y.0);
+        let ground = libm::sqrtf((range * range - height.0 * height.0).max(0.0));
+        Position {
+            x: Millimeters(x * ground / projected),
+            y: Millimeters(y * ground / projected),
+        }
+    }
+
+    fn rotate(&self, x: f32, y: f32) -> (f32, f32) {
+        let (sin, cos) = libm::sincosf(self.yaw_degrees.to_radians());
+        (x * cos + y * sin, y * cos - x * sin)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_near(position: Position, x: f32, y: f32) {
+        assert!(
+            (position.x.0 - x).abs() < 0.5 && (position.y.0 - y).abs() < 0.5,
+            "expected ({}, {}), got {:?}",
+            x,
+            y,
+            position
+        );
+    }
+
+    fn at(x: f32, y: f32) -> Position {
+        Position {
+            x: Millimeters(x),
+            y: Millimeters(y),
+        }
+    }
+
+    #[test]
+    fn test_offset_and_yaw() {
+        // On the left wall at y = 2 m, facing along the room's x axis
+        let pose = MountingPose {
+            position: at(0.0, 2000.0),
+            yaw_degrees: 90.0,
+            ..MountingPose::default()
+        };
+        assert_near(pose.position_to_room(at(0.0, 1000.0)), 1000.0, 2000.0);
+        // The radar's x axis points down the room's y axis
+        assert_near(pose.position_to_room(at(500.0, 1000.0)), 1000.0, 1500.0);
+    }
+
+    #[test]
+    fn test_ground_distance_from_height() {
+        let pose = MountingPose {
+            height: Some(Millimeters(1500.0)),
+            ..MountingPose::default()
+        };
+        assert_near(pose.position_to_room(at(0.0, 2500.0)), 0.0, 2000.0);
+        assert_near(pose.position_to_room(at(100.0, 1000.0)), 0.0, 0.0);
+        // Off axis, the slant range of 2236 mm is corrected, keeping the azimuth
+        assert_near(pose.position_to_room(at(2000.0, 1000.0)), 1483.2, 741.6);
+    }
+
+    #[test]
+    fn test_ground_distance_from_height_and_tilt() {
+        // On the ceiling, tilted down
+        let pose = MountingPose {
+            height: Some(Millimeters(1500.0)),
+            tilt_degrees: 45.0,
+            ..MountingPose::default()
+        };
+        assert_near(pose.position_to_room(at(0.0, 2500.0)), 0.0, 2000.0);
+        // The tilt turns the target away from the normal, at the same ground distance
+        assert_near(pose.position_to_room(at(1000.0, 2000.0)), 957.4, 1354.0);
+    }
+
+    #[test]
+    fn test_ground_distance_from_tilt() {
+        let pose = MountingPose {
+            tilt_degrees: 60.0,
+            ..MountingPose::default()
+        };
+        assert_near(pose.position_to_room(at(0.0, 2000.0)), 0.0, 1000.0);
+    }
+}
@@ -1,4 +1,7 @@
-use crate::RadarError;
+use crate::{
+    units::{CentimetersPerSecond, Millimeters, Position, Velocity, VelocityComponents},
+    RadarError,
+};
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct RadarTarget {
@@ -11,9 +14,10 @@ pub struct RadarTarget {
     ///
     /// This is not the true speed, but the speed component in the direction away from the radar
     /// s_measured = s_true * cos(θ)
-    /// where θ is the angle between the radar normal and the target velocity
+    /// where θ is the angle between the line of sight from the radar to the target and the
+    /// target velocity, see [`Self::radial_velocity`]
     ///
-    /// A target moving tangent to the radar will measure 0 speed
+    /// A target moving across the line of sight, e.g. circling the radar, will measure 0 speed
     pub speed: i16,
     /// The resolution of the distance measurement in mm (distance gate size)
     pub resolution: u16,
@@ -24,6 +28,61 @@ impl RadarTarget {
     pub fn is_untracked(&self) -> bool {
         self.x_coordinate == 0 && self.y_coordinate == 0 && self.speed == 0 && self.resolution == 0
     }
+
+    pub fn x(&self) -> Millimeters {
+        Millimeters(self.x_coordinate.into())
+    }
+
+    pub fn y(&self) -> Millimeters {
+        Millimeters(self.y_coordinate.into())
+    }
+
+    pub fn position(&self) -> Position {
+        Position {
+            x: self.x(),
+            y: self.y(),
+        }
+    }
+
+    /// The straight-line distance from the radar
+    pub fn range(&self) -> Millimeters {
+        Millimeters(libm::hypotf(self.x().0, self.y().0))
+    }
+
+    /// The angle from the radar normal (the y axis) to the target, positive on the +x side
+    pub fn azimuth_radians(&self) -> f32 {
+        libm::atan2f(self.x().0, self.y().0)
+    }
+
+    /// The angle from the radar normal (the y axis) to the target, positive on the +x side
+    pub fn azimuth_degrees(&self) -> f32 {
+        self.azimuth_radians().to_degrees()
+    }
+
+    /// The measured speed, away from the radar
+    pub fn radial_speed(&self) -> CentimetersPerSecond {
+        CentimetersPerSecond(self.speed.into())
+    }
+
+    /// The measured speed as a velocity along the line of sight. The tangential
+    /// part of the target's velocity is not measured, so this is only a lower bound.
+    pub fn radial_velocity(&self) -> Velocity {
+        let (sin, cos) = libm::sincosf(self.azimuth_radians());
+        Velocity {
+            x: self.radial_speed() * sin,
+            y: self.radial_speed() * cos,
+        }
+    }
+
+    /// Splits `velocity`, e.g. from tracking the target over several frames, along and
+    /// across the line of sight. The radial part is what the radar reports as [`Self::speed`].
+    pub fn decompose(&self, velocity: Velocity) -> VelocityComponents {
+        let (sin, cos) = libm::sincosf(self.azimuth_radians());
+        VelocityComponents {
+            radial: velocity.x * sin + velocity.y * cos,
+            tangential: velocity.x * cos - velocity.y * sin,
+        }
+    }
 }
 
 impl TryFrom<&[u8]> for RadarTarget {
@@ -53,16 +112,16 @@ fn i16_from_le_weird_sign(mut data: [u8; 2]) -> i16 {
     }
 }
 
+/// Decodes the three target slots of a data frame, leaving untracked slots empty
 pub(crate) fn decode_radar_targets(
     data: &[u8; 24],
-) -> Result<heapless::Vec<RadarTarget, 3>, RadarError> {
-    let mut targets = heapless::Vec::new();
-    for i in 0..3 {
+) -> Result<[Option<RadarTarget>; 3], RadarError> {
+    let mut targets = [None, None, None];
+    for (i, slot) in targets.iter_mut().enumerate() {
         let range = i * 8..(i + 1) * 8;
         let target = RadarTarget::try_from(&data[range])?;
         if !target.is_untracked() {
-            // Safety: The push will not overflow because of the for loop range
-            unsafe { targets.push_unchecked(target) };
+            *slot = Some(target);
         }
     }
     Ok(targets)
@@ -94,7 +153,47 @@ mod tests {
             0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
         ];
         let targets = decode_radar_targets(&data).unwrap();
-        assert_eq!(targets.len(), 1);
-        assert_eq!(targets[0].x_coordinate, -782);
+        assert_eq!(targets[0].as_ref().unwrap().x_coordinate, -782);
+        assert_eq!(targets[1..], [None, None]);
+    }
+
+    fn target_at(x_coordinate: i16, y_coordinate: i16, speed: i16) -> RadarTarget {
+        RadarTarget {
+            x_coordinate,
+            y_coordinate,
+            speed,
+            resolution: 320,
+        }
+    }
+
+    #[test]
+    fn test_polar_coordinates() {
+        let target = target_at(300, 400, 0);
+        assert_eq!(target.range(), Millimeters(500.0));
+        assert!((target.azimuth_degrees() - 36.87).abs() < 0.01);
+
+        let left = target_at(-1000, 1000, 0);
+        assert!((left.azimuth_radians() + core::f32::consts::FRAC_PI_4).abs() < 1e-6);
+        assert_eq!(target_at(0, 1000, 0).azimuth_degrees(), 0.0);
+    }
+
+    #[test]
+    fn test_velocity_decomposition() {
+        let target = target_at(1000, 1000, -50);
+        let approaching = target.radial_velocity();
+        assert!((approaching.x.0 + 35.355).abs() < 0.01);
+        assert!((approaching.y.0 + 35.355).abs() < 0.01);
+
+        let components = target.decompose(approaching);
+        assert!((components.radial - target.radial_speed()).0.abs() < 1e-4);
+        assert!(components.tangential.0.abs() < 1e-4);
+
+        // Moving along +x at 45°: half radial, half tangential
+        let components = target.decompose(Velocity {
+            x: CentimetersPerSecond(100.0),
+            y: CentimetersPerSecond(0.0),
+        });
+        assert!((components.radial.0 - 70.711).abs() < 0.01);
+        assert!((components.tangential.0 - 70.711).abs() < 0.01);
     }
 }
@@ -0,0 +1,161 @@
+//! Retrying configuration commands whose ACK was lost or corrupted.
+//!
+//! A [`RetryPolicy`] is applied to the commands sent during a configuration session,
+//! and the driver counts the attempts made for each command in [`RetryStats`].
+//! Commands the radar rejects are never retried, as they would be rejected again.
+//!
+//! Commands that change the radar mode, i.e. entering and exiting configuration mode,
+//! restarting and factory resetting the radar, are sent once and not counted. A lost ACK
+//! leaves it unclear whether the radar switched, so these hand back a
+//! [`Desync`](crate::Desync) driver instead, see [`LD2450::recover`](crate::LD2450::recover).
+
+use embedded_hal_async::delay::DelayNs;
+
+/// The number of distinct command words tracked by [`RetryStats`]
+const MAX_TRACKED_COMMANDS: usize = 16;
+
+/// How configuration commands are retried when their ACK is lost.
+/// Mode switches and restarts are never retried, see the [module docs](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of times a command is sent, including the first attempt
+    pub attempts: u8,
+    /// The delay before the first retry, doubling with each further retry.
+    /// Only takes effect when the driver has a delay, see [`LD2450::with_delay`](crate::LD2450::with_delay).
+    pub backoff_ms: u32,
+    /// Whether to re-enter configuration mode before retrying, in case the
+    /// radar left it while the command was lost
+    pub reenter_config: bool,
+}
+
+impl RetryPolicy {
+    /// Sends each command once, reporting the first error
+    pub const NONE: RetryPolicy = RetryPolicy {
+        attempts: 1,
+        backoff_ms: 0,
+        reenter_config: false,
+    };
+
+    /// The delay before retry number `retry`, starting at 1
+    pub(crate) fn backoff_ms(&self, retry: u8) -> u32 {
+        let factor = 1u32.checked_shl(u32::from(retry) - 1).unwrap_or(u32::MAX);
+        self.backoff_ms.saturating_mul(factor)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// The attempts made for a single command word
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CommandStats {
+    /// The number of times the command was sent, including retries
+    pub attempts: u32,
+    /// The number of times the command was sent again after a failed attempt
+    pub retries: u32,
+    /// The number of times the command failed after exhausting its retries
+    pub failures: u32,
+}
+
+/// Per command attempt counts, collected across configuration sessions.
+/// Mode switches and restarts are not retried, and have no entry.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RetryStats {
+    commands: heapless::LinearMap<u16, CommandStats, MAX_TRACKED_COMMANDS>,
+}
+
+impl RetryStats {
+    /// The attempts made for `command`, if it has been sent
+    pub fn get(&self, command: u16) -> Option<CommandStats> {
+        self.commands.get(&command).copied()
+    }
+
+    /// The attempts made for each command word that has been sent
+    pub fn iter(&self) -> impl Iterator<Item = (u16, CommandStats)> + '_ {
+        self.commands
+            .iter()
+            .map(|(&command, &stats)| (command, stats))
+    }
+
+    /// The number of retries across all commands
+    pub fn total_retries(&self) -> u32 {
+        self.commands.values().map(|stats| stats.retries).sum()
+    }
+
+    pub fn clear(&mut self) {
+        self.commands.clear();
+    }
+
+    pub(crate) fn record(&mut self, command: u16, attempts: u8, succeeded: bool) {
+        if !self.commands.contains_key(&command) {
+            // There are fewer command words than tracked commands
+            let _ = self.commands.insert(command, CommandStats::default());
+        }
+        if let Some(stats) = self.commands.get_mut(&command) {
+            stats.attempts += u32::from(attempts);
+            stats.retries += u32::from(attempts - 1);
+            stats.failures += u32::from(!succeeded);
+        }
+    }
+}
+
+/// A delay that returns immediately, used when the driver isn't given one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct NoDelay;
+
+impl DelayNs for NoDelay {
+    async fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(feature = "blocking")]
+impl embedded_hal::delay::DelayNs for NoDelay {
+    fn delay_ns(&mut self, _ns: u32) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles() {
+        let policy = RetryPolicy {
+            attempts: 4,
+            backoff_ms: 50,
+            reenter_config: false,
+        };
+        assert_eq!(policy.backoff_ms(1), 50);
+        assert_eq!(policy.backoff_ms(2), 100);
+        assert_eq!(policy.backoff_ms(3), 200);
+        assert_eq!(policy.backoff_ms(40), u32::MAX);
+    }
+
+    #[test]
+    fn test_record_stats() {
+        let mut stats = RetryStats::default();
+        stats.record(0x91, 1, true);
+        stats.record(0x91, 3, true);
+        stats.record(0xA0, 2, false);
+
+        assert_eq!(
+            stats.get(0x91),
+            Some(CommandStats {
+                attempts: 4,
+                retries: 2,
+                failures: 0,
+            })
+        );
+        assert_eq!(
+            stats.get(0xA0),
+            Some(CommandStats {
+                attempts: 2,
+                retries: 1,
+                failures: 1,
+            })
+        );
+        assert_eq!(stats.get(0xA5), None);
+        assert_eq!(stats.total_retries(), 3);
+    }
+}
@@ -0,0 +1,22 @@
+//! The driver as a [`Stream`] of frames, to combine with other async sources
+
+use embedded_io_async::Read;
+use futures_util::Stream;
+
+use crate::{Frame, NormalMode, RadarError, LD2450};
+
+impl<Serial: Read, Delay> LD2450<Serial, NormalMode, Delay> {
+    /// Reports each data frame, as by [`LD2450::next_frame`].
+    ///
+    /// Errors are reported without ending the stream, except for serial port errors,
+    /// which end it. The driver is available again once the stream is dropped.
+    /// The stream must be pinned to be polled, e.g. with [`core::pin::pin`].
+    pub fn frame_stream(&mut self) -> impl Stream<Item = Result<Frame, RadarError>> + '_ {
+        futures_util::stream::unfold(Some(self), |radar| async move {
+            let radar = radar?;
+            let frame = radar.next_frame().await;
+            let radar = (frame != Err(RadarError::SerialError)).then_some(radar);
+            Some((frame, radar))
+        })
+    }
+}
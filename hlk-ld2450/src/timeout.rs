@@ -0,0 +1,144 @@
+//! Timeouts for serial reads and writes, so an unplugged or silent radar can't block
+//! the driver forever.
+//!
+//! Wrap the serial port in an [`IdleTimeoutSerial`] before handing it to the driver, and
+//! operations that time out fail with [`RadarError::Timeout`](crate::RadarError::Timeout).
+//!
+//! The timeout applies to each read and write on its own, so it bounds how long the serial
+//! port may stay idle, not how long a command takes. A radar that keeps sending data frames,
+//! or trickles bytes, while an ACK is awaited never trips it, and the driver gives up on the
+//! ACK once it has skipped too many bytes instead.
+
+use core::{
+    future::{poll_fn, Future},
+    pin::pin,
+    task::Poll,
+};
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_io_async::{ErrorKind, ErrorType, Read, Write};
+
+/// A serial port that gives up on any read or write not completed within a timeout,
+/// which starts over with every call
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdleTimeoutSerial<Serial, Delay> {
+    serial: Serial,
+    delay: Delay,
+    timeout_us: u32,
+}
+
+/// An error from an [`IdleTimeoutSerial`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeoutError<E> {
+    /// The read or write did not complete in time
+    TimedOut,
+    /// The serial port threw an error
+    Serial(E),
+}
+
+impl<E: embedded_io_async::Error> embedded_io_async::Error for TimeoutError<E> {
+    fn kind(&self) -> ErrorKind {
+        match self {
+            TimeoutError::TimedOut => ErrorKind::TimedOut,
+            TimeoutError::Serial(e) => e.kind(),
+        }
+    }
+}
+
+impl<Serial, Delay: DelayNs> IdleTimeoutSerial<Serial, Delay> {
+    /// Wraps `serial`, limiting each read and write to `timeout_ms`.
+    ///
+    /// The radar reports targets about every 100 ms, but takes longer to come back up
+    /// after a restart, so timeouts of at least 2 s are recommended.
+    pub fn new(serial: Serial, delay: Delay, timeout_ms: u32) -> Self {
+        Self {
+            serial,
+            delay,
+            timeout_us: timeout_ms.saturating_mul(1000),
+        }
+    }
+
+    pub fn set_timeout_ms(&mut self, timeout_ms: u32) {
+        self.timeout_us = timeout_ms.saturating_mul(1000);
+    }
+
+    pub fn get_mut(&mut self) -> &mut Serial {
+        &mut self.serial
+    }
+
+    pub fn into_inner(self) -> (Serial, Delay) {
+        (self.serial, self.delay)
+    }
+}
+
+/// Runs `future` until it completes, or until `delay` elapses
+async fn with_timeout<T, E>(
+    future: impl Future<Output = Result<T, E>>,
+    delay: impl Future<Output = ()>,
+) -> Result<T, TimeoutError<E>> {
+    match with_deadline(future, delay).await {
+        Some(result) => result.map_err(TimeoutError::Serial),
+        None => Err(TimeoutError::TimedOut),
+    }
+}
+
+/// Runs `future` until it completes, or returns `None` once `deadline` completes
+pub(crate) async fn with_deadline<T>(
+    future: impl Future<Output = T>,
+    deadline: impl Future<Output = ()>,
+) -> Option<T> {
+    let mut future = pin!(future);
+    let mut deadline = pin!(deadline);
+    poll_fn(|cx| {
+        if let Poll::Ready(result) = future.as_mut().poll(cx) {
+            return Poll::Ready(Some(result));
+        }
+        deadline.as_mut().poll(cx).map(|()| None)
+    })
+    .await
+}
+
+/// Completes along with `delay`, unless it completes without ever waiting. A delay that
+/// doesn't wait, like [`NoDelay`](crate::retry::NoDelay), measures no time, so it never elapses.
+pub(crate) async fn measured(delay: impl Future<Output = ()>) {
+    let mut delay = pin!(delay);
+    let mut waited = false;
+    let mut never = false;
+    poll_fn(|cx| {
+        if never {
+            return Poll::Pending;
+        }
+        match delay.as_mut().poll(cx) {
+            Poll::Ready(()) if waited => Poll::Ready(()),
+            Poll::Ready(()) => {
+                never = true;
+                Poll::Pending
+            }
+            Poll::Pending => {
+                waited = true;
+                Poll::Pending
+            }
+        }
+    })
+    .await
+}
+
+impl<Serial: ErrorType, Delay> ErrorType for IdleTimeoutSerial<Serial, Delay> {
+    type Error = TimeoutError<Serial::Error>;
+}
+
+impl<Serial: Read, Delay: DelayNs> Read for IdleTimeoutSerial<Serial, Delay> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        with_timeout(self.serial.read(buf), self.delay.delay_us(self.timeout_us)).await
+    }
+}
+
+impl<Serial: Write, Delay: DelayNs> Write for IdleTimeoutSerial<Serial, Delay> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        with_timeout(self.serial.write(buf), self.delay.delay_us(self.timeout_us)).await
+    }
+
+    async fn flush(&mut self) -> Result<(), Self::Error> {
+        with_timeout(self.serial.flush(), self.delay.delay_us(self.timeout_us)).await
+    }
+}
@@ -0,0 +1,449 @@
+//! Stable identities for targets across frames.
+//!
+//! The radar's three target slots don't always follow the same person from frame to
+//! frame. [`Tracker`] associates each frame's targets with the tracks of the previous
+//! frames by distance, so every person keeps the same [`TrackId`].
+//!
+//! ```
+//! use hlk_ld2450::{tracking::{Tracker, TrackerConfig}, RadarTarget};
+//!
+//! let mut tracker: Tracker = Tracker::new(TrackerConfig {
+//!     confirm_frames: 2,
+//!     ..TrackerConfig::default()
+//! });
+//! let target = |x_coordinate| RadarTarget {
+//!     x_coordinate,
+//!     y_coordinate: 1500,
+//!     speed: 0,
+//!     resolution: 320,
+//! };
+//!
+//! tracker.update(&[target(-500)]);
+//! tracker.update(&[target(-480)]);
+//! let person = tracker.confirmed().next().unwrap();
+//! assert_eq!(person.target.x_coordinate, -480);
+//! ```
+
+use crate::{
+    filter::{FilterConfig, KalmanFilter},
+    units::{Millimeters, Position, Velocity},
+    Frame, RadarTarget,
+};
+
+/// The most targets the radar reports in one frame
+const MAX_TARGETS: usize = 3;
+
+/// How tracks are associated with targets, and when they are born and die
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrackerConfig {
+    /// The furthest a target may be from a track to be associated with it
+    pub gate: Millimeters,
+    /// The number of consecutive frames a new track must be seen in to be confirmed
+    pub confirm_frames: u8,
+    /// The number of consecutive frames a confirmed track is kept without being seen
+    pub coast_frames: u8,
+    /// The time between frames, which the radar sends about 10 times per second.
+    /// [`Tracker::update_frame`] only falls back to this without frame timestamps.
+    pub frame_interval_ms: u32,
+    /// The rate of the clock that timestamps frames, see
+    /// [`LD2450::set_clock`](crate::LD2450::set_clock), in ticks per second
+    pub clock_ticks_per_second: u32,
+    /// Smooths the position of each track
+    pub filter: FilterConfig,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            gate: Millimeters(600.0),
+            confirm_frames: 3,
+            coast_frames: 5,
+            frame_interval_ms: 100,
+            clock_ticks_per_second: 1_000_000,
+            filter: FilterConfig::default(),
+        }
+    }
+}
+
+/// Identifies a track for as long as it lives. IDs are not reused until they wrap around.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TrackId(pub u32);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackState {
+    /// Seen in fewer than [`TrackerConfig::confirm_frames`] frames, and dropped when missed
+    Tentative,
+    /// Seen in the latest frame
+    Confirmed,
+    /// Confirmed, but missed in recent frames
+    Coasting,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Track {
+    pub id: TrackId,
+    pub state: TrackState,
+    /// The target as last seen
+    pub target: RadarTarget,
+    /// The number of consecutive frames the track was seen in
+    hits: u8,
+    /// The number of consecutive frames the track was missed in
+    misses: u8,
+    filter: KalmanFilter,
+}
+
+impl Track {
+    /// Whether the track is confirmed, even if it is coasting
+    pub fn is_confirmed(&self) -> bool {
+        self.state != TrackState::Tentative
+    }
+
+    /// The smoothed position, predicted while the track is coasting
+    pub fn position(&self) -> Position {
+        self.filter.position()
+    }
+
+    /// The estimated velocity, including the part across the line of sight
+    pub fn velocity(&self) -> Velocity {
+        self.filter.velocity()
+    }
+
+    fn distance(&self, target: &RadarTarget) -> f32 {
+        let position = self.position();
+        libm::hypotf((position.x - target.x()).0, (position.y - target.y()).0)
+    }
+}
+
+/// Tracks up to `N` targets across frames, including recently missed ones
+#[derive(Debug, Clone)]
+pub struct Tracker<const N: usize = 6> {
+    config: TrackerConfig,
+    tracks: heapless::Vec<Track, N>,
+    next_id: u32,
+    /// The timestamp of the frame passed to [`Self::update_frame`] last
+    last_timestamp: Option<u64>,
+}
+
+impl<const N: usize> Tracker<N> {
+    pub fn new(config: TrackerConfig) -> Self {
+        Self {
+            config,
+            tracks: heapless::Vec::new(),
+            next_id: 0,
+            last_timestamp: None,
+        }
+    }
+
+    /// Associates the targets of the next frame with the tracks, e.g. the result of
+    /// [`LD2450::next_radar_targets`](crate::LD2450::next_radar_targets).
+    ///
+    /// Each target is associated with the nearest track within the gate, closest pairs
+    /// first, comparing with where each track is predicted to be. Targets left over start
+    /// new tracks, unless all `N` tracks are in use.
+    ///
+    /// Tracks are predicted [`TrackerConfig::frame_interval_ms`] ahead, see
+    /// [`Self::update_frame`] to use the time between frames instead.
+    pub fn update<'a>(&mut self, targets: impl IntoIterator<Item = &'a RadarTarget>) {
+        let dt = self.config.frame_interval_ms as f32 / 1000.0;
+        self.update_after(dt, targets);
+    }
+
+    /// Like [`Self::update`], predicting the tracks by the time since the previous frame,
+    /// e.g. the result of [`LD2450::next_frame`](crate::LD2450::next_frame).
+    ///
+    /// The time is measured between frame timestamps, in
+    /// [`TrackerConfig::clock_ticks_per_second`]. For the first frame, frames without a
+    /// timestamp, and timestamps that didn't advance, [`TrackerConfig::frame_interval_ms`]
+    /// is used instead.
+    pub fn update_frame(&mut self, frame: &Frame) {
+        let elapsed = match (self.last_timestamp, frame.timestamp) {
+            (Some(last), Some(now)) if now > last => Some(now - last),
+            _ => None,
+        };
+        let dt = match elapsed {
+            Some(ticks) => ticks as f32 / self.config.clock_ticks_per_second as f32,
+            None => self.config.frame_interval_ms as f32 / 1000.0,
+        };
+        self.last_timestamp = frame.timestamp;
+        self.update_after(dt, frame.tracked());
+    }
+
+    /// Predicts the tracks `dt` seconds ahead, then associates them with `targets`
+    fn update_after<'a>(&mut self, dt: f32, targets: impl IntoIterator<Item = &'a RadarTarget>) {
+        let targets: heapless::Vec<&RadarTarget, MAX_TARGETS> =
+            targets.into_iter().take(MAX_TARGETS).collect();
+        for track in &mut self.tracks {
+            track.filter.predict(&self.config.filter, dt);
+        }
+        let mut matched_tracks = [false; N];
+        let mut matched_targets = [false; MAX_TARGETS];
+
+        while let Some((track, target)) =
+            self.closest_pair(&targets, &matched_tracks, &matched_targets)
+        {
+            matched_tracks[track] = true;
+            matched_targets[target] = true;
+            let track = &mut self.tracks[track];
+            track.filter.update(&self.config.filter, targets[target]);
+            track.target = targets[target].clone();
+        }
+
+        let config = self.config;
+        let mut i = 0;
+        self.tracks.retain_mut(|track| {
+            let matched = matched_tracks[i];
+            i += 1;
+            if matched {
+                track.hits = track.hits.saturating_add(1);
+                track.misses = 0;
+                if track.state == TrackState::Coasting || track.hits >= config.confirm_frames {
+                    track.state = TrackState::Confirmed;
+                }
+                return true;
+            }
+            track.hits = 0;
+            track.misses = track.misses.saturating_add(1);
+            match track.state {
+                TrackState::Tentative => false,
+                _ => {
+                    track.state = TrackState::Coasting;
+                    track.misses <= config.coast_frames
+                }
+            }
+        });
+
+        for (target, _) in targets.iter().zip(matched_targets).filter(|(_, m)| !m) {
+            let state = if config.confirm_frames <= 1 {
+                TrackState::Confirmed
+            } else {
+                TrackState::Tentative
+            };
+            let track = Track {
+                id: TrackId(self.next_id),
+                state,
+                target: (*target).clone(),
+                hits: 1,
+                misses: 0,
+                filter: KalmanFilter::new(&config.filter, target),
+            };
+            if self.tracks.push(track).is_err() {
+                log::warn!("Too many tracks, dropping a target");
+                break;
+            }
+            self.next_id = self.next_id.wrapping_add(1);
+        }
+    }
+
+    /// The closest unmatched track and target within the gate
+    fn closest_pair(
+        &self,
+        targets: &[&RadarTarget],
+        matched_tracks: &[bool],
+        matched_targets: &[bool],
+    ) -> Option<(usize, usize)> {
+        let mut closest = None;
+        let mut closest_distance = self.config.gate.0;
+        for (i, track) in self.tracks.iter().enumerate() {
+            if matched_tracks[i] {
+                continue;
+            }
+            for (j, target) in targets.iter().enumerate() {
+                let distance = track.distance(target);
+                if !matched_targets[j] && distance <= closest_distance {
+                    closest = Some((i, j));
+                    closest_distance = distance;
+                }
+            }
+        }
+        closest
+    }
+
+    /// All live tracks, including tentative ones
+    pub fn tracks(&self) -> &[Track] {
+        &self.tracks
+    }
+
+    /// The confirmed tracks, including coasting ones
+    pub fn confirmed(&self) -> impl Iterator<Item = &Track> {
+        self.tracks.iter().filter(|track| track.is_confirmed())
+    }
+
+    /// Drops all tracks, e.g. after the radar was reconfigured
+    pub fn clear(&mut self) {
+        self.tracks.clear();
+        self.last_timestamp = None;
+    }
+}
+
+impl<const N: usize> Default for Tracker<N> {
+    fn default() -> Self {
+        Self::new(TrackerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn target(x_coordinate: i16, y_coordinate: i16) -> RadarTarget {
+        RadarTarget {
+            x_coordinate,
+            y_coordinate,
+            speed: 0,
+            resolution: 320,
+        }
+    }
+
+    fn ids(tracker: &Tracker) -> heapless::Vec<(u32, i16), 6> {
+        tracker
+            .confirmed()
+            .map(|track| (track.id.0, track.target.x_coordinate))
+            .collect()
+    }
+
+    #[test]
+    fn test_confirmation() {
+        let mut tracker: Tracker = Tracker::default();
+        tracker.update(&[target(0, 1000)]);
+        tracker.update(&[target(10, 1000)]);
+        assert_eq!(tracker.tracks()[0].state, TrackState::Tentative);
+        assert_eq!(ids(&tracker), []);
+
+        tracker.update(&[target(20, 1000)]);
+        assert_eq!(ids(&tracker), [(0, 20)]);
+    }
+
+    #[test]
+    fn test_tentative_track_dies_when_missed() {
+        let mut tracker: Tracker = Tracker::default();
+        tracker.update(&[target(0, 1000)]);
+        tracker.update(&[]);
+        assert!(tracker.tracks().is_empty());
+
+        tracker.update(&[target(0, 1000)]);
+        assert_eq!(tracker.tracks()[0].id, TrackId(1));
+    }
+
+    #[test]
+    fn test_identity_follows_swapped_slots() {
+        let mut tracker: Tracker = Tracker::new(TrackerConfig {
+            confirm_frames: 1,
+            ..TrackerConfig::default()
+        });
+        tracker.update(&[target(-1000, 1000), target(1000, 1000)]);
+        // The radar reports the targets in the other order
+        tracker.update(&[target(1050, 1000), target(-950, 1000)]);
+        assert_eq!(ids(&tracker), [(0, -950), (1, 1050)]);
+    }
+
+    #[test]
+    fn test_coasting_and_death() {
+        let mut tracker: Tracker = Tracker::new(TrackerConfig {
+            confirm_frames: 1,
+            coast_frames: 2,
+            ..TrackerConfig::default()
+        });
+        tracker.update(&[target(0, 1000)]);
+        tracker.update(&[]);
+        tracker.update(&[]);
+        assert_eq!(tracker.tracks()[0].state, TrackState::Coasting);
+
+        // Reappearing within the coasting period keeps the identity
+        tracker.update(&[target(100, 1000)]);
+        assert_eq!(tracker.tracks()[0].state, TrackState::Confirmed);
+        assert_eq!(ids(&tracker), [(0, 100)]);
+
+        for _ in 0..3 {
+            tracker.update(&[]);
+        }
+        assert!(tracker.tracks().is_empty());
+    }
+
+    #[test]
+    fn test_gate() {
+        let mut tracker: Tracker = Tracker::new(TrackerConfig {
+            confirm_frames: 1,
+            ..TrackerConfig::default()
+        });
+        tracker.update(&[target(0, 1000)]);
+        // Too far to be the same person
+        tracker.update(&[target(0, 2000)]);
+        assert_eq!(
+            tracker
+                .tracks()
+                .iter()
+                .map(|track| (track.id.0, track.state))
+                .collect::<heapless::Vec<_, 6>>(),
+            [(0, TrackState::Coasting), (1, TrackState::Confirmed)]
+        );
+    }
+
+    #[test]
+    fn test_gate_follows_predicted_position() {
+        let mut tracker: Tracker = Tracker::new(TrackerConfig {
+            confirm_frames: 1,
+            gate: Millimeters(300.0),
+            ..TrackerConfig::default()
+        });
+        // Walking along the x axis at 1 m/s
+        for frame in 0..10 {
+            tracker.update(&[target(frame * 100, 1000)]);
+        }
+        // Missing two frames moves the last position further than the gate
+        tracker.update(&[]);
+        tracker.update(&[]);
+        tracker.update(&[target(1250, 1000)]);
+        assert_eq!(ids(&tracker), [(0, 1250)]);
+
+        let track = &tracker.tracks()[0];
+        assert!((track.velocity().x.0 - 100.0).abs() < 10.0);
+        assert!((track.position().x.0 - 1250.0).abs() < 50.0);
+    }
+
+    fn frame(x_coordinate: i16, timestamp: Option<u64>) -> Frame {
+        Frame {
+            targets: [Some(target(x_coordinate, 1000)), None, None],
+            sequence: 0,
+            timestamp,
+        }
+    }
+
+    #[test]
+    fn test_update_frame_uses_timestamps() {
+        let mut tracker: Tracker = Tracker::new(TrackerConfig {
+            confirm_frames: 1,
+            ..TrackerConfig::default()
+        });
+        // Walking along the x axis at 1 m/s, with frames 200 ms apart in microseconds
+        for frame_number in 0..10 {
+            let timestamp = frame_number as u64 * 200_000;
+            tracker.update_frame(&frame(frame_number * 200, Some(timestamp)));
+        }
+        let track = &tracker.tracks()[0];
+        assert!((track.velocity().x.0 - 100.0).abs() < 10.0);
+    }
+
+    #[test]
+    fn test_update_frame_falls_back_to_frame_interval() {
+        let mut tracker: Tracker = Tracker::new(TrackerConfig {
+            confirm_frames: 1,
+            ..TrackerConfig::default()
+        });
+        // Without timestamps, frames are assumed to be 100 ms apart
+        for frame_number in 0..10 {
+            tracker.update_frame(&frame(frame_number * 100, None));
+        }
+        let track = &tracker.tracks()[0];
+        assert!((track.velocity().x.0 - 100.0).abs() < 10.0);
+    }
+
+    #[test]
+    fn test_capacity() {
+        let mut tracker = Tracker::<2>::new(TrackerConfig {
+            confirm_frames: 1,
+            ..TrackerConfig::default()
+        });
+        tracker.update(&[target(-2000, 1000), target(0, 1000), target(2000, 1000)]);
+        assert_eq!(tracker.tracks().len(), 2);
+    }
+}
@@ -0,0 +1,85 @@
+//! Distances and speeds tagged with their unit, as the radar reports them.
+//!
+//! The radar reports positions in mm and speeds in cm/s, so keeping them in separate
+//! types prevents adding one to the other.
+
+use core::ops::{Add, Mul, Neg, Sub};
+
+/// A distance in mm
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Millimeters(pub f32);
+
+/// A speed in cm/s
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct CentimetersPerSecond(pub f32);
+
+impl Millimeters {
+    pub fn meters(self) -> f32 {
+        self.0 / 1000.0
+    }
+}
+
+impl CentimetersPerSecond {
+    pub fn meters_per_second(self) -> f32 {
+        self.0 / 100.0
+    }
+}
+
+macro_rules! impl_arithmetic {
+    ($unit:ident) => {
+        impl Add for $unit {
+            type Output = Self;
+            fn add(self, rhs: Self) -> Self {
+                Self(self.0 + rhs.0)
+            }
+        }
+
+        impl Sub for $unit {
+            type Output = Self;
+            fn sub(self, rhs: Self) -> Self {
+                Self(self.0 - rhs.0)
+            }
+        }
+
+        impl Neg for $unit {
+            type Output = Self;
+            fn neg(self) -> Self {
+                Self(-self.0)
+            }
+        }
+
+        /// Scales the value, e.g. by a sine or cosine
+        impl Mul<f32> for $unit {
+            type Output = Self;
+            fn mul(self, rhs: f32) -> Self {
+                Self(self.0 * rhs)
+            }
+        }
+    };
+}
+
+impl_arithmetic!(Millimeters);
+impl_arithmetic!(CentimetersPerSecond);
+
+/// A position in the radar's x/y plane, or in a room, see [`MountingPose`](crate::pose::MountingPose)
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Position {
+    pub x: Millimeters,
+    pub y: Millimeters,
+}
+
+/// A velocity in the radar's x/y plane
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Velocity {
+    pub x: CentimetersPerSecond,
+    pub y: CentimetersPerSecond,
+}
+
+/// A velocity split along and across the line of sight from the radar to a target
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct VelocityComponents {
+    /// Away from the radar, as reported by [`RadarTarget::speed`](crate::RadarTarget::speed)
+    pub radial: CentimetersPerSecond,
+    /// Across the line of sight, positive towards increasing azimuth (the +x side)
+    pub tangential: CentimetersPerSecond,
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use common::{set_host_baud_rate, MockRadar};
+use hlk_ld2450::{BaudRate, RadarError, Resynced, LD2450};
+
+#[tokio::test]
+async fn test_change_baud_rate() {
+    let radar = LD2450::new_recycled_config(MockRadar::new())
+        .enter_config()
+        .await
+        .unwrap();
+
+    let mut radar = radar
+        .change_baud_rate(
+            BaudRate::Baud256000,
+            BaudRate::Baud115200,
+            &mut set_host_baud_rate,
+        )
+        .await
+        .unwrap();
+    assert_eq!(radar.next_radar_targets().await.unwrap().len(), 1);
+
+    let radar = radar.into_inner();
+    assert_eq!(radar.baud_rate(), BaudRate::Baud115200);
+    assert_eq!(radar.host_baud_rate, BaudRate::Baud115200);
+    assert_eq!(radar.commands, [0x00FF, 0x00A1, 0x00A3]);
+}
+
+#[tokio::test]
+async fn test_change_baud_rate_rolls_back() {
+    let radar = LD2450::new_recycled_config(MockRadar::ignoring_baud_rate())
+        .enter_config()
+        .await
+        .unwrap();
+
+    let Err((radar, _)) = radar
+        .change_baud_rate(
+            BaudRate::Baud256000,
+            BaudRate::Baud115200,
+            &mut set_host_baud_rate,
+        )
+        .await
+    else {
+        panic!("the radar never switched baud rate");
+    };
+
+    let mut radar = radar.recover().await.unwrap();
+    assert_eq!(radar.next_radar_targets().await.unwrap().len(), 1);
+    assert_eq!(radar.into_inner().host_baud_rate, BaudRate::Baud256000);
+}
+
+#[tokio::test]
+async fn test_change_baud_rate_host_error() {
+    let radar = LD2450::new_recycled_config(MockRadar::new())
+        .enter_config()
+        .await
+        .unwrap();
+
+    let mut unsupported = |radar: &mut MockRadar, baud_rate: BaudRate| match baud_rate {
+        BaudRate::Baud9600 => Err("unsupported"),
+        _ => set_host_baud_rate(radar, baud_rate).map_err(|_| "unreachable"),
+    };
+    let Err((radar, error)) = radar
+        .change_baud_rate(BaudRate::Baud256000, BaudRate::Baud9600, &mut unsupported)
+        .await
+    else {
+        panic!("the host could not change baud rate");
+    };
+    assert_eq!(error, RadarError::SerialError);
+
+    // Nothing was sent, so the radar can still be reached at its current rate
+    let Ok(Resynced::Configuration(radar)) = radar.resync().await else {
+        panic!("the radar should still be in configuration mode");
+    };
+    let radar = radar.exit_config().await.unwrap().into_inner();
+    assert_eq!(radar.baud_rate(), BaudRate::Baud256000);
+    assert_eq!(radar.host_baud_rate, BaudRate::Baud256000);
+    assert!(!radar.commands.contains(&0x00A1));
+}
+
+#[tokio::test]
+async fn test_change_baud_rate_rollback_only_reverts_host() {
+    let radar = LD2450::new_recycled_config(MockRadar::new())
+        .enter_config()
+        .await
+        .unwrap();
+
+    // The host accepts the new rate, but its serial port never switches over
+    let mut broken = |radar: &mut MockRadar, baud_rate: BaudRate| match baud_rate {
+        BaudRate::Baud115200 => Ok(()),
+        _ => set_host_baud_rate(radar, baud_rate),
+    };
+    let Err((radar, _)) = radar
+        .change_baud_rate(BaudRate::Baud256000, BaudRate::Baud115200, &mut broken)
+        .await
+    else {
+        panic!("no data frames should have been received");
+    };
+
+    let radar = radar.into_inner();
+    assert_eq!(radar.host_baud_rate, BaudRate::Baud256000);
+    assert_eq!(radar.baud_rate(), BaudRate::Baud115200);
+}
+
+#[tokio::test]
+async fn test_detect_baud_rate_in_normal_mode() {
+    let radar = MockRadar::new().with_baud_rate(BaudRate::Baud57600);
+
+    let Ok((Resynced::Normal(mut radar), baud_rate)) =
+        LD2450::detect_baud_rate(radar, &mut set_host_baud_rate).await
+    else {
+        panic!("the radar should have been found in normal mode");
+    };
+    assert_eq!(baud_rate, BaudRate::Baud57600);
+    assert_eq!(radar.next_radar_targets().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_detect_baud_rate_in_configuration_mode() {
+    let radar = MockRadar::in_config_mode().with_baud_rate(BaudRate::Baud460800);
+
+    let Ok((Resynced::Configuration(mut radar), baud_rate)) =
+        LD2450::detect_baud_rate(radar, &mut set_host_baud_rate).await
+    else {
+        panic!("the radar should have been found in configuration mode");
+    };
+    assert_eq!(baud_rate, BaudRate::Baud460800);
+    radar.tracking_mode().await.unwrap();
+    assert_eq!(radar.into_inner().commands, [0x0091, 0x0091]);
+}
+
+#[tokio::test]
+async fn test_detect_baud_rate_skips_unsupported_rates() {
+    let radar = MockRadar::new().with_baud_rate(BaudRate::Baud9600);
+    let mut tried = Vec::new();
+    let mut host = |radar: &mut MockRadar, baud_rate: BaudRate| {
+        tried.push(baud_rate);
+        if baud_rate == BaudRate::Baud256000 {
+            return Err(());
+        }
+        radar.host_baud_rate = baud_rate;
+        Ok(())
+    };
+
+    let Ok((_, baud_rate)) = LD2450::detect_baud_rate(radar, &mut host).await else {
+        panic!("the radar should have been found");
+    };
+    assert_eq!(baud_rate, BaudRate::Baud9600);
+    assert_eq!(tried, [BaudRate::Baud256000, BaudRate::Baud9600]);
+}
+
+#[tokio::test]
+async fn test_detect_baud_rate_without_radar() {
+    let radar = MockRadar::in_config_mode().with_baud_rate(BaudRate::Baud9600);
+    let mut host = |_: &mut MockRadar, _: BaudRate| Ok::<_, ()>(());
+
+    let Err((_, error)) = LD2450::detect_baud_rate(radar, &mut host).await else {
+        panic!("the host never switched to the radar's baud rate");
+    };
+    assert_eq!(error, RadarError::Desyncronized);
+}
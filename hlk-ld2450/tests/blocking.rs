@@ -0,0 +1,94 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{
+    success_ack, MockRadar, MockSerial, DATA_FRAME, ENTER_CONFIG, ENTER_CONFIG_ACK, EXIT_CONFIG,
+    EXIT_CONFIG_ACK,
+};
+use hlk_ld2450::{
+    blocking::{Resynced, LD2450},
+    config::TargetTrackingMode,
+    retry::{CommandStats, RetryPolicy},
+    Config,
+};
+
+#[test]
+fn test_next_radar_targets() {
+    let data = [&[0x00, 0xFF][..], &DATA_FRAME].concat();
+    let mut radar = LD2450::new_recycled_config(MockSerial::<0>::new(&data));
+
+    let targets = radar.next_radar_targets().unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].x_coordinate, -782);
+    assert_eq!(targets[0].y_coordinate, 1713);
+}
+
+#[test]
+fn test_new_applies_config() {
+    let data = [
+        &ENTER_CONFIG_ACK[..],
+        &success_ack(0x90),
+        &success_ack(0xC2),
+        &EXIT_CONFIG_ACK,
+    ]
+    .concat();
+    let serial = MockSerial::<128>::new(&data);
+
+    let radar = LD2450::new(serial, Config::default()).ok().unwrap();
+
+    let multi_target_tracking = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0x90, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let no_zone_filtering = [
+        &[0xFD, 0xFC, 0xFB, 0xFA, 0x1C, 0x00, 0xC2, 0x00][..],
+        &[0x00; 26],
+        &[0x04, 0x03, 0x02, 0x01],
+    ]
+    .concat();
+    let expected = [
+        &ENTER_CONFIG[..],
+        &multi_target_tracking,
+        &no_zone_filtering,
+        &EXIT_CONFIG,
+    ]
+    .concat();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
+
+#[test]
+fn test_recover_from_stale_configuration_mode() {
+    let mut radar = MockRadar::in_config_mode();
+    radar.reject_command(0x00FF);
+    let Err((radar, _)) = LD2450::new_recycled_config(radar).enter_config() else {
+        panic!("entering configuration mode should have failed");
+    };
+
+    let Ok(Resynced::Configuration(radar)) = radar.resync() else {
+        panic!("the radar should have been found in configuration mode");
+    };
+    let mut radar = radar.exit_config().unwrap();
+    assert_eq!(radar.next_radar_targets().unwrap().len(), 1);
+    assert!(!radar.into_inner().is_config_mode());
+}
+
+#[test]
+fn test_retry_after_lost_ack() {
+    let mut radar = MockRadar::new();
+    radar.lose_acks(0x0091, 1);
+    let mut radar = LD2450::new_recycled_config(radar).enter_config().unwrap();
+    radar.set_retry_policy(RetryPolicy {
+        attempts: 2,
+        ..RetryPolicy::NONE
+    });
+
+    assert_eq!(radar.tracking_mode().unwrap(), TargetTrackingMode::Multiple);
+    assert_eq!(
+        radar.retry_stats().get(0x91),
+        Some(CommandStats {
+            attempts: 2,
+            retries: 1,
+            failures: 0,
+        })
+    );
+}
@@ -0,0 +1,95 @@
+#![cfg(feature = "codec")]
+
+mod common;
+
+use bytes::BytesMut;
+use common::DATA_FRAME;
+use futures::StreamExt;
+use hlk_ld2450::{
+    codec::{Codec, Command},
+    config::{BluetoothPassword, FilteringMode, TargetTrackingMode},
+    decoder::Event,
+    BaudRate, RadarError,
+};
+use tokio_util::codec::{Encoder, FramedRead};
+
+const TRACKING_MODE_ACK: [u8; 16] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x06, 0x00, 0x91, 0x01, 0x00, 0x00, 0x02, 0x00, 0x04, 0x03, 0x02, 0x01,
+];
+
+fn encode(command: Command) -> Vec<u8> {
+    let mut dst = BytesMut::new();
+    Codec::new().encode(command, &mut dst).unwrap();
+    dst.to_vec()
+}
+
+#[tokio::test]
+async fn test_decode_frames_split_across_reads() {
+    let serial = tokio_test::io::Builder::new()
+        .read(&DATA_FRAME[..10])
+        .read(&[&DATA_FRAME[10..], &TRACKING_MODE_ACK[..3]].concat())
+        .read(&TRACKING_MODE_ACK[3..])
+        .build();
+    let mut frames = FramedRead::new(serial, Codec::new());
+
+    let Some(Ok(Ok(Event::Targets([Some(target), None, None])))) = frames.next().await else {
+        panic!("expected targets");
+    };
+    assert_eq!(target.x_coordinate, -782);
+    let Some(Ok(Ok(Event::Ack(ack)))) = frames.next().await else {
+        panic!("expected an ACK");
+    };
+    assert_eq!(ack.command, 0x0191);
+    assert_eq!(&ack.data[..], &[0x02, 0x00]);
+    assert!(frames.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_corrupted_frame_does_not_end_stream() {
+    let mut corrupted = DATA_FRAME;
+    corrupted[29] = 0x00;
+    let serial = tokio_test::io::Builder::new()
+        .read(&corrupted)
+        .read(&DATA_FRAME)
+        .build();
+    let mut frames = FramedRead::new(serial, Codec::new());
+
+    assert_eq!(
+        frames.next().await.unwrap().unwrap(),
+        Err(RadarError::UnexpectedFrameSize)
+    );
+    assert!(matches!(
+        frames.next().await,
+        Some(Ok(Ok(Event::Targets(_))))
+    ));
+}
+
+#[test]
+fn test_encode_commands() {
+    assert_eq!(
+        encode(Command::EnterConfig),
+        [0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xFF, 0x00, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01]
+    );
+    assert_eq!(
+        encode(Command::SetTrackingMode(TargetTrackingMode::Single)),
+        [0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0x80, 0x00, 0x04, 0x03, 0x02, 0x01]
+    );
+    assert_eq!(
+        encode(Command::SetBaudRate(BaudRate::Baud115200)),
+        [0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA1, 0x00, 0x05, 0x00, 0x04, 0x03, 0x02, 0x01]
+    );
+    assert_eq!(
+        encode(Command::SetBluetoothPassword(BluetoothPassword::FACTORY)),
+        [
+            0xFD, 0xFC, 0xFB, 0xFA, 0x08, 0x00, 0xA9, 0x00, b'H', b'i', b'L', b'i', b'n', b'k',
+            0x04, 0x03, 0x02, 0x01
+        ]
+    );
+
+    let zone_filtering = encode(Command::SetZoneFiltering(FilteringMode::None));
+    assert_eq!(
+        &zone_filtering[..8],
+        [0xFD, 0xFC, 0xFB, 0xFA, 0x1C, 0x00, 0xC2, 0x00]
+    );
+    assert_eq!(zone_filtering.len(), 8 + 26 + 4);
+}
@@ -1,6 +1,16 @@
+// Each test binary only uses some of the helpers
+#![allow(dead_code)]
+
+use hlk_ld2450::BaudRate;
+
+/// A serial port that replays `data`, and records up to `LEN` written bytes
+#[derive(Debug)]
 pub struct MockSerial<'a, const LEN: usize> {
     data: &'a [u8],
     position: usize,
+    written: heapless::Vec<u8, LEN>,
+    /// Waits forever for more data once `data` is replayed, instead of reaching its end
+    silent_at_end: bool,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -14,20 +24,350 @@ impl embedded_io_async::Error for MockSerialError {
 
 impl<'a, const LEN: usize> MockSerial<'a, LEN> {
     pub fn new(data: &'a [u8]) -> Self {
-        Self { data, position: 0 }
+        Self {
+            data,
+            position: 0,
+            written: heapless::Vec::new(),
+            silent_at_end: false,
+        }
+    }
+
+    /// Replays `data`, then waits forever like a radar that stopped sending
+    pub fn then_silent(data: &'a [u8]) -> Self {
+        Self {
+            silent_at_end: true,
+            ..Self::new(data)
+        }
+    }
+
+    /// The bytes written to the serial port so far
+    pub fn written(&self) -> &[u8] {
+        &self.written
     }
 }
 
-impl<const LEN: usize> embedded_io_async::Read for MockSerial<'_, LEN> {
-    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+impl<const LEN: usize> MockSerial<'_, LEN> {
+    fn replay(&mut self, buf: &mut [u8]) -> Result<usize, MockSerialError> {
         let view = &self.data[self.position..];
         let len = buf.len().min(view.len());
         buf[..len].copy_from_slice(&view[..len]);
         self.position += len;
         Ok(len)
     }
+
+    fn record(&mut self, buf: &[u8]) -> Result<usize, MockSerialError> {
+        self.written
+            .extend_from_slice(buf)
+            .map_err(|_| MockSerialError)?;
+        Ok(buf.len())
+    }
+}
+
+impl<const LEN: usize> embedded_io_async::Read for MockSerial<'_, LEN> {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        if self.silent_at_end && self.position == self.data.len() {
+            std::future::pending().await
+        }
+        self.replay(buf)
+    }
+}
+
+impl<const LEN: usize> embedded_io_async::Write for MockSerial<'_, LEN> {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.record(buf)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<const LEN: usize> embedded_io::Read for MockSerial<'_, LEN> {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.replay(buf)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl<const LEN: usize> embedded_io::Write for MockSerial<'_, LEN> {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.record(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
 }
 
 impl<const LEN: usize> embedded_io_async::ErrorType for MockSerial<'_, LEN> {
     type Error = MockSerialError;
 }
+
+/// A data frame with a single target
+pub const DATA_FRAME: [u8; 30] = [
+    0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x55, 0xCC,
+];
+
+// Configuration commands and their ACKs
+pub const ENTER_CONFIG: [u8; 14] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xFF, 0x00, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01,
+];
+pub const ENTER_CONFIG_ACK: [u8; 18] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x08, 0x00, 0xFF, 0x01, 0x00, 0x00, 0x01, 0x00, 0x40, 0x00, 0x04, 0x03,
+    0x02, 0x01,
+];
+pub const EXIT_CONFIG: [u8; 12] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0xFE, 0x00, 0x04, 0x03, 0x02, 0x01,
+];
+pub const EXIT_CONFIG_ACK: [u8; 14] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xFE, 0x01, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01,
+];
+pub const RESTART: [u8; 12] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0xA3, 0x00, 0x04, 0x03, 0x02, 0x01,
+];
+pub const FIRMWARE_QUERY: [u8; 12] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0xA0, 0x00, 0x04, 0x03, 0x02, 0x01,
+];
+/// V1.02.22062416, as in the datasheet
+pub const FIRMWARE_V1_ACK: [u8; 22] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x0C, 0x00, 0xA0, 0x01, 0x00, 0x00, 0x00, 0x00, 0x02, 0x01, 0x16, 0x24,
+    0x06, 0x22, 0x04, 0x03, 0x02, 0x01,
+];
+
+/// A successful ACK of `command`, without any payload
+pub fn success_ack(command: u8) -> [u8; 14] {
+    [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, command, 0x01, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ]
+}
+
+/// A simulated radar that reports targets in normal mode, and acknowledges
+/// commands in configuration mode
+#[derive(Debug, Default)]
+pub struct MockRadar {
+    config_mode: bool,
+    /// Ignores requests to leave configuration mode
+    stuck_in_config: bool,
+    /// A command to reject once, without acting on it
+    rejected_command: Option<u16>,
+    /// A command whose next ACKs are lost, as if corrupted by line noise
+    lost_acks: Option<(u16, usize)>,
+    /// Acknowledges baud rate changes without storing them
+    ignore_baud_rate: bool,
+    baud_rate: BaudRate,
+    stored_baud_rate: BaudRate,
+    /// The baud rate of the host serial port. Frames sent at another rate are received as garbage.
+    pub host_baud_rate: BaudRate,
+    pending: std::collections::VecDeque<u8>,
+    command: Vec<u8>,
+    /// Every command word understood, whether or not it was acknowledged
+    pub commands: Vec<u16>,
+}
+
+impl MockRadar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A radar that was left in configuration mode
+    pub fn in_config_mode() -> Self {
+        Self {
+            config_mode: true,
+            ..Self::default()
+        }
+    }
+
+    /// A radar that never leaves configuration mode
+    pub fn stuck_in_config() -> Self {
+        Self {
+            config_mode: true,
+            stuck_in_config: true,
+            ..Self::default()
+        }
+    }
+
+    /// Has the radar communicate at `baud_rate`, while the host starts at the default rate
+    pub fn with_baud_rate(self, baud_rate: BaudRate) -> Self {
+        Self {
+            baud_rate,
+            stored_baud_rate: baud_rate,
+            ..self
+        }
+    }
+
+    /// A radar that acknowledges baud rate changes, but keeps its current rate
+    pub fn ignoring_baud_rate() -> Self {
+        Self {
+            ignore_baud_rate: true,
+            ..Self::default()
+        }
+    }
+
+    pub fn baud_rate(&self) -> BaudRate {
+        self.baud_rate
+    }
+
+    pub fn is_config_mode(&self) -> bool {
+        self.config_mode
+    }
+
+    /// Rejects the next `command` with a failure status
+    pub fn reject_command(&mut self, command: u16) {
+        self.rejected_command = Some(command);
+    }
+
+    /// Acts on the next `count` of `command` without acknowledging them
+    pub fn lose_acks(&mut self, command: u16, count: usize) {
+        self.lost_acks = Some((command, count));
+    }
+
+    fn handle_command(&mut self, command: u16, data: &[u8]) {
+        // commands sent at another baud rate are garbage
+        if self.baud_rate != self.host_baud_rate {
+            return;
+        }
+        self.commands.push(command);
+        if !self.config_mode && command != 0x00FF {
+            return;
+        }
+        if self.rejected_command == Some(command) {
+            self.rejected_command = None;
+            self.ack(command, 0x0001, &[]);
+            return;
+        }
+
+        let payload: &[u8] = match command {
+            0x00FF => {
+                self.config_mode = true;
+                &[0x01, 0x00, 0x40, 0x00]
+            }
+            0x00FE if self.stuck_in_config => return,
+            0x00FE => {
+                self.config_mode = false;
+                &[]
+            }
+            // multi target tracking
+            0x0091 => &[0x02, 0x00],
+            0x00A1 => {
+                if !self.ignore_baud_rate {
+                    self.stored_baud_rate = baud_rate_from_repr(data[0]);
+                }
+                &[]
+            }
+            0x00A2 => {
+                self.stored_baud_rate = BaudRate::default();
+                &[]
+            }
+            0x00A3 => {
+                self.ack(command, 0x0000, &[]);
+                self.config_mode = false;
+                self.baud_rate = self.stored_baud_rate;
+                return;
+            }
+            _ => &[],
+        };
+        self.ack(command, 0x0000, payload);
+    }
+
+    fn ack(&mut self, command: u16, status: u16, payload: &[u8]) {
+        if let Some((lost, count)) = &mut self.lost_acks {
+            if *lost == command && *count > 0 {
+                *count -= 1;
+                return;
+            }
+        }
+        let length = 4 + payload.len() as u16;
+        self.pending.extend([0xFD, 0xFC, 0xFB, 0xFA]);
+        self.pending.extend(length.to_le_bytes());
+        self.pending.extend((command | 0x0100).to_le_bytes());
+        self.pending.extend(status.to_le_bytes());
+        self.pending.extend(payload);
+        self.pending.extend([0x04, 0x03, 0x02, 0x01]);
+    }
+}
+
+/// Switches the baud rate of the host end of a [`MockRadar`], as a [`hlk_ld2450::SetBaudRate`]
+pub fn set_host_baud_rate(radar: &mut MockRadar, baud_rate: BaudRate) -> Result<(), ()> {
+    radar.host_baud_rate = baud_rate;
+    Ok(())
+}
+
+fn baud_rate_from_repr(repr: u8) -> BaudRate {
+    match repr {
+        0x01 => BaudRate::Baud9600,
+        0x02 => BaudRate::Baud19200,
+        0x03 => BaudRate::Baud38400,
+        0x04 => BaudRate::Baud57600,
+        0x05 => BaudRate::Baud115200,
+        0x06 => BaudRate::Baud230400,
+        0x07 => BaudRate::Baud256000,
+        0x08 => BaudRate::Baud460800,
+        _ => panic!("unknown baud rate {:#x}", repr),
+    }
+}
+
+impl MockRadar {
+    fn transmit(&mut self, buf: &mut [u8]) -> Result<usize, MockSerialError> {
+        // configuration mode goes silent once every ACK has been read
+        if self.pending.is_empty() && !self.config_mode {
+            if self.baud_rate == self.host_baud_rate {
+                self.pending.extend(DATA_FRAME);
+            } else {
+                self.pending.extend([0x00; DATA_FRAME.len()]);
+            }
+        }
+        let len = buf.len().min(self.pending.len());
+        for (dst, src) in buf.iter_mut().zip(self.pending.drain(..len)) {
+            *dst = src;
+        }
+        Ok(len)
+    }
+
+    fn receive(&mut self, buf: &[u8]) -> Result<usize, MockSerialError> {
+        self.command.extend_from_slice(buf);
+        // header, length, command word and EOF
+        while self.command.len() >= 12 {
+            let length = u16::from_le_bytes([self.command[4], self.command[5]]) as usize;
+            let frame_len = 6 + length + 4;
+            if self.command.len() < frame_len {
+                break;
+            }
+            let command = u16::from_le_bytes([self.command[6], self.command[7]]);
+            let frame: Vec<u8> = self.command.drain(..frame_len).collect();
+            self.handle_command(command, &frame[8..frame_len - 4]);
+        }
+        Ok(buf.len())
+    }
+}
+
+impl embedded_io_async::Read for MockRadar {
+    async fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.transmit(buf)
+    }
+}
+
+impl embedded_io_async::Write for MockRadar {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.receive(buf)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl embedded_io::Read for MockRadar {
+    fn read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error> {
+        self.transmit(buf)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl embedded_io::Write for MockRadar {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        self.receive(buf)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+impl embedded_io_async::ErrorType for MockRadar {
+    type Error = MockSerialError;
+}
@@ -0,0 +1,483 @@
+mod common;
+
+use common::{
+    success_ack, MockSerial, DATA_FRAME, ENTER_CONFIG, ENTER_CONFIG_ACK, EXIT_CONFIG,
+    EXIT_CONFIG_ACK, FIRMWARE_QUERY, FIRMWARE_V1_ACK, RESTART,
+};
+use hlk_ld2450::{
+    config::{BluetoothPassword, FilteredRegion, FilteringMode, TargetTrackingMode},
+    Config, ConfigError, FirmwareVersion, MacAddress, ProtocolError, RadarError, LD2450,
+};
+
+#[tokio::test]
+async fn test_tracking_mode_query() {
+    let ack = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x06, 0x00, 0x91, 0x01, 0x00, 0x00, 0x01, 0x00, 0x04, 0x03, 0x02,
+        0x01,
+    ];
+    let data = [&ENTER_CONFIG_ACK[..], &ack, &EXIT_CONFIG_ACK].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    let mode = radar.tracking_mode().await.unwrap();
+    assert_eq!(mode, TargetTrackingMode::Single);
+
+    let query = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0x91, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let expected = [&ENTER_CONFIG[..], &query, &EXIT_CONFIG].concat();
+    let radar = radar.exit_config().await.unwrap();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
+
+#[tokio::test]
+async fn test_firmware_version_query() {
+    let data = [&ENTER_CONFIG_ACK[..], &FIRMWARE_V1_ACK, &EXIT_CONFIG_ACK].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    let version = radar.firmware_version().await.unwrap();
+    assert_eq!(
+        version,
+        FirmwareVersion {
+            firmware_type: 0,
+            major: 0x0102,
+            minor: 0x22062416,
+        }
+    );
+    let expected = [&ENTER_CONFIG[..], &FIRMWARE_QUERY].concat();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
+
+#[tokio::test]
+async fn test_mac_address_query() {
+    let ack = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x0A, 0x00, 0xA5, 0x01, 0x00, 0x00, 0x8F, 0x27, 0x2E, 0xB8, 0x0F,
+        0x65, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let data = [&ENTER_CONFIG_ACK[..], &ack].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    let mac_address = radar.mac_address().await.unwrap();
+    assert_eq!(
+        mac_address,
+        MacAddress::from([0x8F, 0x27, 0x2E, 0xB8, 0x0F, 0x65])
+    );
+
+    let query = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA5, 0x00, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let expected = [&ENTER_CONFIG[..], &query].concat();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
+
+#[tokio::test]
+async fn test_bluetooth_password() {
+    let permission_denied = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA8, 0x01, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let data = [
+        &ENTER_CONFIG_ACK[..],
+        &success_ack(0xA9),
+        &permission_denied,
+        &success_ack(0xA8),
+    ]
+    .concat();
+    let serial = MockSerial::<128>::new(&data);
+
+    let password = BluetoothPassword::new("s3cret").unwrap();
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    radar.set_bluetooth_password(&password).await.unwrap();
+    assert!(!radar
+        .bluetooth_permission(&BluetoothPassword::FACTORY)
+        .await
+        .unwrap());
+    assert!(radar.bluetooth_permission(&password).await.unwrap());
+
+    let set_password = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x08, 0x00, 0xA9, 0x00, b's', b'3', b'c', b'r', b'e', b't', 0x04,
+        0x03, 0x02, 0x01,
+    ];
+    let factory_permission = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x08, 0x00, 0xA8, 0x00, b'H', b'i', b'L', b'i', b'n', b'k', 0x04,
+        0x03, 0x02, 0x01,
+    ];
+    let permission = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x08, 0x00, 0xA8, 0x00, b's', b'3', b'c', b'r', b'e', b't', 0x04,
+        0x03, 0x02, 0x01,
+    ];
+    let expected = [
+        &ENTER_CONFIG[..],
+        &set_password,
+        &factory_permission,
+        &permission,
+    ]
+    .concat();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
+
+#[tokio::test]
+async fn test_zone_filtering_query() {
+    let no_zone_filtering_ack = [
+        &[0xFD, 0xFC, 0xFB, 0xFA, 0x1E, 0x00, 0xC1, 0x01, 0x00, 0x00][..],
+        &[0x00; 26],
+        &[0x04, 0x03, 0x02, 0x01],
+    ]
+    .concat();
+    let data = [&ENTER_CONFIG_ACK[..], &no_zone_filtering_ack].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    assert_eq!(radar.zone_filtering().await, Ok(FilteringMode::None));
+
+    let query = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0xC1, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let expected = [&ENTER_CONFIG[..], &query].concat();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
+
+#[tokio::test]
+async fn test_ack_after_data_frame() {
+    // the radar may still be reporting targets when the config command is sent
+    let frame = [
+        0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x55, 0xCC,
+    ];
+    let ack = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA4, 0x01, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let data = [&frame[..], &ENTER_CONFIG_ACK, &ack, &EXIT_CONFIG_ACK].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    assert_eq!(radar.set_bluetooth_enabled(true).await, Ok(()));
+    let radar = radar.exit_config().await.unwrap();
+
+    let set_bluetooth = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA4, 0x00, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let expected = [&ENTER_CONFIG[..], &set_bluetooth, &EXIT_CONFIG].concat();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
+
+#[tokio::test]
+async fn test_command_rejected() {
+    let ack = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA4, 0x01, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let data = [&ENTER_CONFIG_ACK[..], &ack, &EXIT_CONFIG_ACK].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    let result = radar.set_bluetooth_enabled(false).await;
+    assert_eq!(
+        result,
+        Err(RadarError::Protocol(ProtocolError::CommandFailed {
+            command: 0xA4,
+            status: 0x0001,
+        }))
+    );
+    // the session continues after a rejected command
+    assert!(radar.exit_config().await.is_ok());
+}
+
+#[tokio::test]
+async fn test_unexpected_command_echo_skipped() {
+    // ACK for the multi target tracking command instead of single target tracking
+    let ack = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0x90, 0x01, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let data = [&ENTER_CONFIG_ACK[..], &ack, &EXIT_CONFIG_ACK].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    // ACKs for other commands are discarded while waiting for the reply, which never comes
+    let result = radar.set_tracking_mode(TargetTrackingMode::Single).await;
+    assert_eq!(
+        result,
+        Err(RadarError::Protocol(ProtocolError::UnexpectedEof))
+    );
+}
+
+#[tokio::test]
+async fn test_ack_bad_trailer() {
+    let ack = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA4, 0x01, 0x00, 0x00, 0x04, 0x03, 0x02, 0x00,
+    ];
+    let data = [&ENTER_CONFIG_ACK[..], &ack, &EXIT_CONFIG_ACK].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    let result = radar.set_bluetooth_enabled(true).await;
+    assert_eq!(result, Err(RadarError::Protocol(ProtocolError::BadTrailer)));
+}
+
+#[tokio::test]
+async fn test_query_length_mismatch() {
+    // tracking mode ACK without the mode
+    let ack = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0x91, 0x01, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let data = [&ENTER_CONFIG_ACK[..], &ack, &EXIT_CONFIG_ACK].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    let result = radar.tracking_mode().await;
+    assert_eq!(
+        result,
+        Err(RadarError::Protocol(ProtocolError::LengthMismatch {
+            expected: 6,
+            actual: 4,
+        }))
+    );
+}
+
+#[tokio::test]
+async fn test_new_applies_config() {
+    let data = [
+        &ENTER_CONFIG_ACK[..],
+        &success_ack(0x90),
+        &success_ack(0xA4),
+        &success_ack(0xC2),
+        &success_ack(0xA3),
+        &DATA_FRAME,
+    ]
+    .concat();
+    let serial = MockSerial::<128>::new(&data);
+
+    let config = Config {
+        bluetooth_enabled: Some(false),
+        ..Default::default()
+    };
+    let radar = LD2450::new(serial, config).await.ok().unwrap();
+
+    let multi_target_tracking = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0x90, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let bluetooth_disabled = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA4, 0x00, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let no_zone_filtering = [
+        &[0xFD, 0xFC, 0xFB, 0xFA, 0x1C, 0x00, 0xC2, 0x00][..],
+        &[0x00; 26],
+        &[0x04, 0x03, 0x02, 0x01],
+    ]
+    .concat();
+    let expected = [
+        &ENTER_CONFIG[..],
+        &multi_target_tracking,
+        &bluetooth_disabled,
+        &no_zone_filtering,
+        // the Bluetooth setting is applied by restarting
+        &RESTART,
+    ]
+    .concat();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
+
+#[tokio::test]
+async fn test_new_applies_zone_filtering() {
+    let data = [
+        &ENTER_CONFIG_ACK[..],
+        &success_ack(0x90),
+        &success_ack(0xC2),
+        &EXIT_CONFIG_ACK,
+    ]
+    .concat();
+    let serial = MockSerial::<128>::new(&data);
+
+    let region = FilteredRegion::new(-1000, 0, 1000, 2000).unwrap();
+    let config = Config {
+        filtering_mode: FilteringMode::Inside(heapless::Vec::from_slice(&[region]).unwrap()),
+        ..Default::default()
+    };
+    let radar = LD2450::new(serial, config).await.ok().unwrap();
+
+    let serial = radar.into_inner();
+    // after entering configuration mode, and setting the tracking mode
+    let zone_filtering = &serial.written()[ENTER_CONFIG.len() + 12..];
+    // the command word, and filtering inside the region
+    assert_eq!(&zone_filtering[6..10], &[0xC2, 0x00, 0x02, 0x00]);
+}
+
+#[tokio::test]
+async fn test_new_reports_failed_setting() {
+    let bluetooth_rejected = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA4, 0x01, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let data = [
+        &ENTER_CONFIG_ACK[..],
+        &success_ack(0x80),
+        &bluetooth_rejected,
+        &EXIT_CONFIG_ACK,
+    ]
+    .concat();
+    let serial = MockSerial::<128>::new(&data);
+
+    let config = Config {
+        tracking: TargetTrackingMode::Single,
+        bluetooth_enabled: Some(true),
+        ..Default::default()
+    };
+    let Err((serial, error)) = LD2450::new(serial, config).await else {
+        panic!("the bluetooth setting should have failed");
+    };
+    assert_eq!(
+        error,
+        ConfigError::Bluetooth(RadarError::Protocol(ProtocolError::CommandFailed {
+            command: 0xA4,
+            status: 0x0001,
+        }))
+    );
+    // configuration mode is exited without attempting the remaining settings
+    assert!(serial.written().ends_with(&EXIT_CONFIG));
+}
+
+#[tokio::test]
+async fn test_read_config() {
+    let tracking_mode_ack = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x06, 0x00, 0x91, 0x01, 0x00, 0x00, 0x02, 0x00, 0x04, 0x03, 0x02,
+        0x01,
+    ];
+    let zone_filtering_ack = [
+        &[
+            0xFD, 0xFC, 0xFB, 0xFA, 0x1E, 0x00, 0xC1, 0x01, 0x00, 0x00, 0x01, 0x00,
+        ][..],
+        // (-1000, 0) to (1000, 2000)
+        &[0x18, 0xFC, 0x00, 0x00, 0xE8, 0x03, 0xD0, 0x07],
+        &[0x00; 16],
+        &[0x04, 0x03, 0x02, 0x01],
+    ]
+    .concat();
+    let data = [
+        &ENTER_CONFIG_ACK[..],
+        &tracking_mode_ack,
+        &zone_filtering_ack,
+        &EXIT_CONFIG_ACK,
+    ]
+    .concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    let config = radar.read_config().await.unwrap();
+    assert_eq!(config.tracking, TargetTrackingMode::Multiple);
+    assert_eq!(config.bluetooth_enabled, None);
+    match config.filtering_mode {
+        FilteringMode::Outside(regions) => assert_eq!(regions.len(), 1),
+        other => panic!("expected outside filtering, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_zone_filtering_round_trip() {
+    let zone_filtering = [
+        &[0x01, 0x00][..],
+        // (-1000, 0) to (1000, 2000)
+        &[0x18, 0xFC, 0x00, 0x00, 0xE8, 0x03, 0xD0, 0x07],
+        &[0x00; 16],
+    ]
+    .concat();
+    let zone_filtering_ack = [
+        &[0xFD, 0xFC, 0xFB, 0xFA, 0x1E, 0x00, 0xC1, 0x01, 0x00, 0x00][..],
+        &zone_filtering,
+        &[0x04, 0x03, 0x02, 0x01],
+    ]
+    .concat();
+    let data = [
+        &ENTER_CONFIG_ACK[..],
+        &success_ack(0xC2),
+        &zone_filtering_ack,
+        &EXIT_CONFIG_ACK,
+    ]
+    .concat();
+    let serial = MockSerial::<128>::new(&data);
+
+    let region = FilteredRegion::new(-1000, 0, 1000, 2000).unwrap();
+    let mode = FilteringMode::Outside(heapless::Vec::from_slice(&[region]).unwrap());
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    radar.set_zone_filtering(&mode).await.unwrap();
+    assert_eq!(radar.zone_filtering().await.unwrap(), mode);
+
+    let set_zone_filtering = [
+        &[0xFD, 0xFC, 0xFB, 0xFA, 0x1C, 0x00, 0xC2, 0x00][..],
+        &zone_filtering,
+        &[0x04, 0x03, 0x02, 0x01],
+    ]
+    .concat();
+    let query = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0xC1, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let expected = [&ENTER_CONFIG[..], &set_zone_filtering, &query, &EXIT_CONFIG].concat();
+    let radar = radar.exit_config().await.unwrap();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
+
+#[tokio::test]
+async fn test_failed_enter_config_recovers() {
+    let enter_config_rejected = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xFF, 0x01, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let frame = [
+        0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x55, 0xCC,
+    ];
+    // the radar stays in normal mode, ignoring the probe
+    let data = [&enter_config_rejected[..], &frame, &frame, &frame].concat();
+    let serial = MockSerial::<64>::new(&data);
+
+    let Err((radar, error)) = LD2450::new_recycled_config(serial).enter_config().await else {
+        panic!("entering configuration mode should have failed");
+    };
+    assert_eq!(
+        error,
+        RadarError::Protocol(ProtocolError::CommandFailed {
+            command: 0xFF,
+            status: 0x0001,
+        })
+    );
+
+    let radar = radar.recover().await.unwrap();
+    let probe = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0x91, 0x00, 0x04, 0x03, 0x02, 0x01,
+    ];
+    let expected = [&ENTER_CONFIG[..], &probe].concat();
+    assert_eq!(radar.into_inner().written(), &expected[..]);
+}
@@ -0,0 +1,68 @@
+mod common;
+
+use common::MockRadar;
+use hlk_ld2450::{Desync, Resynced, LD2450};
+
+/// Puts a driver into the desynchronized state by failing to enter configuration mode
+async fn desynced(mut radar: MockRadar) -> LD2450<MockRadar, Desync> {
+    radar.reject_command(0x00FF);
+    let Err((radar, _)) = LD2450::new_recycled_config(radar).enter_config().await else {
+        panic!("entering configuration mode should have failed");
+    };
+    radar
+}
+
+#[tokio::test]
+async fn test_resync_detects_configuration_mode() {
+    let radar = desynced(MockRadar::in_config_mode()).await;
+
+    let Ok(Resynced::Configuration(radar)) = radar.resync().await else {
+        panic!("the radar should have been found in configuration mode");
+    };
+    assert!(radar.into_inner().is_config_mode());
+}
+
+#[tokio::test]
+async fn test_resync_detects_normal_mode() {
+    let radar = desynced(MockRadar::new()).await;
+
+    let Ok(Resynced::Normal(radar)) = radar.resync().await else {
+        panic!("the radar should have been found in normal mode");
+    };
+    assert!(!radar.into_inner().is_config_mode());
+}
+
+#[tokio::test]
+async fn test_recover_from_stale_configuration_mode() {
+    let radar = desynced(MockRadar::in_config_mode()).await;
+
+    let mut radar = radar.recover().await.unwrap();
+    let targets = radar.next_radar_targets().await.unwrap();
+    assert_eq!(targets.len(), 1);
+
+    let radar = radar.into_inner();
+    assert!(!radar.is_config_mode());
+    assert_eq!(radar.commands.last(), Some(&0x00FE));
+}
+
+#[tokio::test]
+async fn test_recover_config_from_normal_mode() {
+    let radar = desynced(MockRadar::new()).await;
+
+    let mut radar = radar.recover_config().await.unwrap();
+    radar.tracking_mode().await.unwrap();
+
+    let radar = radar.into_inner();
+    assert!(radar.is_config_mode());
+    assert_eq!(radar.commands, [0x00FF, 0x0091, 0x00FF, 0x0091]);
+}
+
+#[tokio::test]
+async fn test_recover_fails_when_stuck_in_configuration_mode() {
+    let radar = desynced(MockRadar::stuck_in_config()).await;
+
+    let Err((radar, _)) = radar.recover().await else {
+        panic!("recovery should have failed");
+    };
+    assert!(radar.into_inner().is_config_mode());
+}
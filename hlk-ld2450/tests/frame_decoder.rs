@@ -0,0 +1,177 @@
+use hlk_ld2450::{
+    decoder::{Decoder, Event},
+    ProtocolError, RadarError, RadarTarget,
+};
+
+fn single_target(event: Result<Event, RadarError>) -> RadarTarget {
+    match event {
+        Ok(Event::Targets([Some(target), None, None])) => target,
+        other => panic!("expected targets, got {:?}", other),
+    }
+}
+
+fn assert_expected_target(target: RadarTarget) {
+    assert_eq!(target.x_coordinate, -782);
+    assert_eq!(target.y_coordinate, 1713);
+    assert_eq!(target.speed, -16);
+    assert_eq!(target.resolution, 320);
+}
+
+#[test]
+fn test_decode_radar_targets() {
+    let data: [u8; 30] = [
+        0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x55, 0xCC,
+    ];
+
+    let mut decoder = Decoder::new();
+    let mut events = decoder.feed(&data);
+    assert_expected_target(single_target(events.next().unwrap()));
+    assert!(events.next().is_none());
+}
+
+#[test]
+fn test_decode_radar_targets_out_of_sync() {
+    let data: [u8; 35] = [
+        0x00, 0x00, 0xFF, 0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x55, 0xCC, 0xFF, 0xAA,
+    ];
+
+    let mut decoder = Decoder::new();
+    let mut events = decoder.feed(&data);
+    assert_expected_target(single_target(events.next().unwrap()));
+    assert!(events.next().is_none());
+}
+
+#[test]
+fn test_decode_radar_targets_sneaky_out_of_sync() {
+    // partial header match
+    let data: [u8; 35] = [
+        0xAA, 0xFF, 0xFF, 0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x55, 0xCC, 0xFF, 0xAA,
+    ];
+
+    let mut decoder = Decoder::new();
+    let mut events = decoder.feed(&data);
+    assert_expected_target(single_target(events.next().unwrap()));
+    assert!(events.next().is_none());
+}
+
+#[test]
+fn test_decode_radar_targets_invalid_length() {
+    let data: [u8; 35] = [
+        0xAA, 0xFF, 0xFF, 0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x55, 0xCC, 0xFF,
+    ];
+
+    let mut decoder = Decoder::new();
+    let mut events = decoder.feed(&data);
+    assert_eq!(events.next(), Some(Err(RadarError::UnexpectedFrameSize)));
+    assert!(events.next().is_none());
+}
+
+#[test]
+fn test_decode_split_across_chunks() {
+    let data: [u8; 62] = [
+        0x55, 0xCC, 0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x55, 0xCC, 0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x55, 0xCC,
+    ];
+
+    for chunk_size in 1..data.len() {
+        let mut decoder = Decoder::new();
+        let mut targets = 0;
+        for chunk in data.chunks(chunk_size) {
+            for event in decoder.feed(chunk) {
+                assert_expected_target(single_target(event));
+                targets += 1;
+            }
+        }
+        assert_eq!(targets, 2, "chunk size {}", chunk_size);
+    }
+}
+
+#[test]
+fn test_decode_ack_between_frames() {
+    let data: [u8; 50] = [
+        0xAA, 0xFF, 0x03, 0x00, 0x0E, 0x03, 0xB1, 0x86, 0x10, 0x00, 0x40, 0x01, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x55, 0xCC,
+        0xFD, 0xFC, 0xFB, 0xFA, 0x06, 0x00, 0x91, 0x01, 0x00, 0x00, 0x02, 0x00, 0x04, 0x03, 0x02,
+        0x01, 0xFD, 0xFC, 0xFB, 0xFA,
+    ];
+
+    let mut decoder = Decoder::new();
+    let mut events = decoder.feed(&data);
+    assert_expected_target(single_target(events.next().unwrap()));
+    match events.next() {
+        Some(Ok(Event::Ack(ack))) => {
+            assert_eq!(ack.command, 0x0191);
+            assert_eq!(ack.status, 0);
+            assert_eq!(&ack.data[..], &[0x02, 0x00]);
+        }
+        other => panic!("expected an ACK, got {:?}", other),
+    }
+    assert!(events.next().is_none());
+    // the trailing header is kept, waiting for the ACK length
+    assert_eq!(decoder.bytes_needed(), 2);
+}
+
+#[test]
+fn test_decode_ack_bad_trailer() {
+    let data: [u8; 14] = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x04, 0x00, 0xA4, 0x01, 0x00, 0x00, 0x04, 0x03, 0x02, 0x00,
+    ];
+
+    let mut decoder = Decoder::new();
+    let mut events = decoder.feed(&data);
+    assert_eq!(
+        events.next(),
+        Some(Err(RadarError::Protocol(ProtocolError::BadTrailer)))
+    );
+    assert!(events.next().is_none());
+}
+
+#[test]
+fn test_decode_ack_too_long() {
+    let data: [u8; 18] = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x00, 0x01, 0xA0, 0x01, 0x00, 0x00, 0x04, 0x03, 0x02, 0x01, 0xFD,
+        0xFC, 0xFB, 0xFA,
+    ];
+
+    let mut decoder = Decoder::new();
+    let mut events = decoder.feed(&data);
+    assert_eq!(
+        events.next(),
+        Some(Err(RadarError::Protocol(ProtocolError::InvalidLength {
+            actual: 256
+        })))
+    );
+    // the decoder looks for the next header
+    assert!(events.next().is_none());
+    assert_eq!(decoder.bytes_needed(), 2);
+}
+
+#[test]
+fn test_bytes_needed_stops_at_frame_end() {
+    let data: [u8; 18] = [
+        0xFD, 0xFC, 0xFB, 0xFA, 0x08, 0x00, 0xFF, 0x01, 0x00, 0x00, 0x01, 0x00, 0x40, 0x00, 0x04,
+        0x03, 0x02, 0x01,
+    ];
+
+    let mut decoder = Decoder::new();
+    let mut position = 0;
+    let mut acks = 0;
+    while position < data.len() {
+        let len = decoder.bytes_needed();
+        let chunk = &data[position..position + len];
+        position += len;
+        acks += decoder.feed(chunk).count();
+    }
+    assert_eq!(position, data.len());
+    assert_eq!(acks, 1);
+}
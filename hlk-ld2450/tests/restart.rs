@@ -0,0 +1,122 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{set_host_baud_rate, success_ack, MockRadar, MockSerial, ENTER_CONFIG_ACK};
+use hlk_ld2450::{BaudRate, ProtocolError, RadarError, LD2450};
+
+/// A delay that elapses as soon as it is polled again, so timeouts expire without waiting
+#[derive(Debug)]
+struct InstantDelay;
+
+impl embedded_hal_async::delay::DelayNs for InstantDelay {
+    async fn delay_ns(&mut self, _ns: u32) {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn test_reboot() {
+    let radar = LD2450::new_recycled_config(MockRadar::new())
+        .enter_config()
+        .await
+        .unwrap();
+
+    let mut radar = radar.reboot().await.unwrap();
+    assert_eq!(radar.next_radar_targets().await.unwrap().len(), 1);
+
+    let radar = radar.into_inner();
+    assert!(!radar.is_config_mode());
+    assert_eq!(radar.commands, [0x00FF, 0x00A3]);
+}
+
+#[tokio::test]
+async fn test_reboot_rejected() {
+    let mut radar = MockRadar::new();
+    radar.reject_command(0x00A3);
+    let radar = LD2450::new_recycled_config(radar)
+        .enter_config()
+        .await
+        .unwrap();
+
+    let Err((radar, error)) = radar.reboot().await else {
+        panic!("the restart was rejected");
+    };
+    assert_eq!(
+        error,
+        RadarError::Protocol(ProtocolError::CommandFailed {
+            command: 0xA3,
+            status: 0x0001,
+        })
+    );
+    let radar = radar.recover().await.unwrap();
+    assert!(!radar.into_inner().is_config_mode());
+}
+
+#[tokio::test]
+async fn test_reboot_without_data_frames() {
+    let mut radar = LD2450::new_recycled_config(MockRadar::new())
+        .enter_config()
+        .await
+        .unwrap();
+    radar
+        .set_serial_baud_rate(BaudRate::Baud115200)
+        .await
+        .unwrap();
+
+    // The radar comes back at the new baud rate, which the host receives as garbage
+    let Err((_, error)) = radar.reboot().await else {
+        panic!("no data frames should have been received");
+    };
+    assert_eq!(error, RadarError::NoDataFrames);
+}
+
+#[tokio::test]
+async fn test_reboot_times_out_without_data() {
+    let data = [&ENTER_CONFIG_ACK[..], &success_ack(0xA3)].concat();
+    // The radar never comes back, and the serial port doesn't time out
+    let serial = MockSerial::<64>::then_silent(&data);
+    let radar = LD2450::new_recycled_config(serial)
+        .with_delay(InstantDelay)
+        .enter_config()
+        .await
+        .unwrap();
+
+    let Err((_, error)) = radar.reboot().await else {
+        panic!("no data frames should have been received");
+    };
+    assert_eq!(error, RadarError::NoDataFrames);
+}
+
+#[tokio::test]
+async fn test_reboot_without_delay_waits_for_serial_port() {
+    let data = [&ENTER_CONFIG_ACK[..], &success_ack(0xA3)].concat();
+    let serial = MockSerial::<64>::then_silent(&data);
+    let radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+
+    // NoDelay can't measure the timeout, which is left to the serial port
+    let reboot = tokio::time::timeout(Duration::from_millis(50), radar.reboot());
+    assert!(reboot.await.is_err());
+}
+
+#[tokio::test]
+async fn test_factory_reset_restores_baud_rate() {
+    let mut radar = MockRadar::new().with_baud_rate(BaudRate::Baud115200);
+    radar.host_baud_rate = BaudRate::Baud115200;
+    let radar = LD2450::new_recycled_config(radar)
+        .enter_config()
+        .await
+        .unwrap();
+
+    let (mut radar, baud_rate) = radar.factory_reset(&mut set_host_baud_rate).await.unwrap();
+    assert_eq!(baud_rate, BaudRate::Baud256000);
+    assert_eq!(radar.next_radar_targets().await.unwrap().len(), 1);
+
+    let radar = radar.into_inner();
+    assert_eq!(radar.baud_rate(), BaudRate::Baud256000);
+    assert_eq!(radar.host_baud_rate, BaudRate::Baud256000);
+    assert_eq!(radar.commands, [0x00FF, 0x00A2, 0x00A3]);
+}
@@ -0,0 +1,158 @@
+mod common;
+
+use std::sync::{Arc, Mutex};
+
+use common::{success_ack, MockRadar, MockSerial, ENTER_CONFIG_ACK, FIRMWARE_V1_ACK};
+use hlk_ld2450::{
+    config::TargetTrackingMode,
+    retry::{CommandStats, RetryPolicy},
+    ProtocolError, RadarError, LD2450,
+};
+
+/// Records the delays requested by the driver, without waiting
+#[derive(Debug, Clone, Default)]
+struct RecordingDelay(Arc<Mutex<Vec<u32>>>);
+
+impl embedded_hal_async::delay::DelayNs for RecordingDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0.lock().unwrap().push(ns / 1_000_000);
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.0.lock().unwrap().push(ms);
+    }
+}
+
+const POLICY: RetryPolicy = RetryPolicy {
+    attempts: 3,
+    backoff_ms: 20,
+    reenter_config: false,
+};
+
+#[tokio::test]
+async fn test_retry_after_lost_ack() {
+    let delay = RecordingDelay::default();
+    let mut radar = MockRadar::new();
+    radar.lose_acks(0x0091, 1);
+    let mut radar = LD2450::new_recycled_config(radar)
+        .with_delay(delay.clone())
+        .enter_config()
+        .await
+        .unwrap();
+    radar.set_retry_policy(POLICY);
+
+    let mode = radar.tracking_mode().await.unwrap();
+    assert_eq!(mode, TargetTrackingMode::Multiple);
+    assert_eq!(
+        radar.retry_stats().get(0x91),
+        Some(CommandStats {
+            attempts: 2,
+            retries: 1,
+            failures: 0,
+        })
+    );
+    assert_eq!(*delay.0.lock().unwrap(), [20]);
+}
+
+#[tokio::test]
+async fn test_retries_exhausted() {
+    let delay = RecordingDelay::default();
+    let mut radar = MockRadar::new();
+    radar.lose_acks(0x0080, 3);
+    let mut radar = LD2450::new_recycled_config(radar)
+        .with_delay(delay.clone())
+        .enter_config()
+        .await
+        .unwrap();
+    radar.set_retry_policy(POLICY);
+
+    assert_eq!(
+        radar.set_tracking_mode(TargetTrackingMode::Single).await,
+        Err(RadarError::Protocol(ProtocolError::UnexpectedEof))
+    );
+    assert_eq!(
+        radar.retry_stats().get(0x80),
+        Some(CommandStats {
+            attempts: 3,
+            retries: 2,
+            failures: 1,
+        })
+    );
+    assert_eq!(*delay.0.lock().unwrap(), [20, 40]);
+}
+
+#[tokio::test]
+async fn test_rejected_command_is_not_retried() {
+    let mut radar = MockRadar::new();
+    radar.reject_command(0x00A4);
+    let mut radar = LD2450::new_recycled_config(radar)
+        .enter_config()
+        .await
+        .unwrap();
+    radar.set_retry_policy(POLICY);
+
+    assert_eq!(
+        radar.set_bluetooth_enabled(false).await,
+        Err(RadarError::Protocol(ProtocolError::CommandFailed {
+            command: 0xA4,
+            status: 0x0001,
+        }))
+    );
+    assert_eq!(radar.retry_stats().get(0xA4).unwrap().attempts, 1);
+}
+
+#[tokio::test]
+async fn test_retry_reenters_config() {
+    let mut radar = MockRadar::new();
+    radar.lose_acks(0x0091, 1);
+    let mut radar = LD2450::new_recycled_config(radar)
+        .enter_config()
+        .await
+        .unwrap();
+    radar.set_retry_policy(RetryPolicy {
+        reenter_config: true,
+        ..POLICY
+    });
+
+    radar.tracking_mode().await.unwrap();
+    assert_eq!(radar.retry_stats().total_retries(), 1);
+    assert_eq!(
+        radar.into_inner().commands,
+        [0x00FF, 0x0091, 0x00FF, 0x0091]
+    );
+}
+
+#[tokio::test]
+async fn test_no_retries_by_default() {
+    let mut radar = MockRadar::new();
+    radar.lose_acks(0x0091, 1);
+    let mut radar = LD2450::new_recycled_config(radar)
+        .enter_config()
+        .await
+        .unwrap();
+
+    assert!(radar.tracking_mode().await.is_err());
+    assert_eq!(radar.retry_stats().get(0x91).unwrap().failures, 1);
+}
+
+#[tokio::test]
+async fn test_stale_ack_skipped() {
+    // A late ACK for an earlier attempt that timed out arrives before the reply
+    let data = [&ENTER_CONFIG_ACK[..], &success_ack(0xA4), &FIRMWARE_V1_ACK].concat();
+    let mut radar = LD2450::new_recycled_config(MockSerial::<64>::new(&data))
+        .enter_config()
+        .await
+        .unwrap();
+    radar.set_retry_policy(POLICY);
+
+    let version = radar.firmware_version().await.unwrap();
+    assert_eq!(version.major, 0x0102);
+    assert_eq!(
+        radar.retry_stats().get(0xA0),
+        Some(CommandStats {
+            attempts: 1,
+            retries: 0,
+            failures: 0,
+        })
+    );
+}
@@ -0,0 +1,114 @@
+#![cfg(all(feature = "std", unix))]
+
+mod common;
+
+use std::{
+    error::Error,
+    fs::File,
+    io::{Read, Write},
+    mem,
+    os::fd::FromRawFd,
+    ptr, thread,
+};
+
+use common::{DATA_FRAME, ENTER_CONFIG, ENTER_CONFIG_ACK, EXIT_CONFIG, EXIT_CONFIG_ACK};
+use hlk_ld2450::{
+    adapters::FromStd, blocking::LD2450, config::TargetTrackingMode, ConfigError, ProtocolError,
+    RadarError,
+};
+
+const TRACKING_QUERY: [u8; 12] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x02, 0x00, 0x91, 0x00, 0x04, 0x03, 0x02, 0x01,
+];
+const SINGLE_TRACKING_ACK: [u8; 16] = [
+    0xFD, 0xFC, 0xFB, 0xFA, 0x06, 0x00, 0x91, 0x01, 0x00, 0x00, 0x01, 0x00, 0x04, 0x03, 0x02, 0x01,
+];
+
+/// Opens a pseudo-terminal in raw mode, returning the radar's end and the host's end
+fn pty_pair() -> (File, File) {
+    let (mut radar, mut host) = (0, 0);
+    // Safety: the file descriptors are owned by the returned files
+    unsafe {
+        let opened = libc::openpty(
+            &mut radar,
+            &mut host,
+            ptr::null_mut(),
+            ptr::null(),
+            ptr::null(),
+        );
+        assert_eq!(opened, 0, "failed to open a pseudo-terminal");
+        let mut termios = mem::zeroed();
+        assert_eq!(libc::tcgetattr(host, &mut termios), 0);
+        libc::cfmakeraw(&mut termios);
+        assert_eq!(libc::tcsetattr(host, libc::TCSANOW, &termios), 0);
+        (File::from_raw_fd(radar), File::from_raw_fd(host))
+    }
+}
+
+/// Acknowledges each expected command with its reply. The radar's end is returned, as
+/// closing it would discard the replies that haven't been read yet.
+fn simulate_radar(mut radar: File, exchanges: &[(&[u8], &[u8])]) -> File {
+    for (command, ack) in exchanges {
+        let mut received = vec![0; command.len()];
+        radar.read_exact(&mut received).unwrap();
+        assert_eq!(&received, command);
+        radar.write_all(ack).unwrap();
+    }
+    radar
+}
+
+#[test]
+fn test_targets_over_pty() {
+    let (mut radar, host) = pty_pair();
+    radar.write_all(&DATA_FRAME).unwrap();
+
+    let mut driver = LD2450::new_recycled_config(FromStd::new(host));
+    let targets = driver.next_radar_targets().unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].x_coordinate, -782);
+}
+
+#[test]
+fn test_config_commands_over_pty() {
+    let (radar, host) = pty_pair();
+    let radar = thread::spawn(move || {
+        simulate_radar(
+            radar,
+            &[
+                (&ENTER_CONFIG, &ENTER_CONFIG_ACK),
+                (&TRACKING_QUERY, &SINGLE_TRACKING_ACK),
+                (&EXIT_CONFIG, &EXIT_CONFIG_ACK),
+            ],
+        )
+    });
+
+    let mut driver = LD2450::new_recycled_config(FromStd::new(host))
+        .enter_config()
+        .unwrap();
+    assert_eq!(driver.tracking_mode().unwrap(), TargetTrackingMode::Single);
+    driver.exit_config().unwrap();
+    radar.join().unwrap();
+}
+
+#[cfg(feature = "tokio")]
+#[tokio::test]
+async fn test_tokio_targets_over_pty() {
+    let (mut radar, host) = pty_pair();
+    radar.write_all(&DATA_FRAME).unwrap();
+
+    let host = hlk_ld2450::adapters::FromTokio::new(tokio::fs::File::from_std(host));
+    let mut driver = hlk_ld2450::LD2450::new_recycled_config(host);
+    let targets = driver.next_radar_targets().await.unwrap();
+    assert_eq!(targets.len(), 1);
+    assert_eq!(targets[0].x_coordinate, -782);
+}
+
+#[test]
+fn test_error_sources() {
+    let error = ConfigError::TrackingMode(RadarError::Protocol(ProtocolError::BadTrailer));
+    assert_eq!(error.to_string(), "could not set the tracking mode");
+
+    let source = error.source().unwrap();
+    assert_eq!(source.to_string(), "configuration command failed");
+    assert_eq!(source.source().unwrap().to_string(), "ACK without an EOF");
+}
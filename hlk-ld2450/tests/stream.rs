@@ -0,0 +1,37 @@
+#![cfg(feature = "stream")]
+
+mod common;
+
+use core::pin::pin;
+
+use common::{MockRadar, MockSerial, DATA_FRAME};
+use futures::StreamExt;
+use hlk_ld2450::{RadarError, LD2450};
+
+#[tokio::test]
+async fn test_stream_ends_on_serial_error() {
+    let data = [&DATA_FRAME[..], &DATA_FRAME].concat();
+    let mut radar = LD2450::new_recycled_config(MockSerial::<0>::new(&data));
+
+    let mut frames = pin!(radar.frame_stream());
+    assert_eq!(frames.next().await.unwrap().unwrap().sequence, 0);
+    assert_eq!(frames.next().await.unwrap().unwrap().sequence, 1);
+    assert_eq!(frames.next().await, Some(Err(RadarError::SerialError)));
+    assert_eq!(frames.next().await, None);
+}
+
+#[tokio::test]
+async fn test_stream_combinators() {
+    let mut radar = LD2450::new_recycled_config(MockRadar::new());
+
+    let x_coordinates: Vec<i16> = radar
+        .frame_stream()
+        .filter_map(|frame| async move { Some(frame.ok()?.targets[0].as_ref()?.x_coordinate) })
+        .take(3)
+        .collect()
+        .await;
+    assert_eq!(x_coordinates, [-782; 3]);
+
+    // The driver is usable again once the stream is dropped
+    assert_eq!(radar.next_radar_targets().await.unwrap().len(), 1);
+}
@@ -1,6 +1,8 @@
 mod common;
 
-use common::MockSerial;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use common::{MockSerial, DATA_FRAME};
 use hlk_ld2450::{RadarError, LD2450};
 
 #[tokio::test]
@@ -72,3 +74,39 @@ async fn test_ld2450_next_radar_targets_invalid_length() {
 
     assert_eq!(targets, Err(RadarError::UnexpectedFrameSize));
 }
+
+#[tokio::test]
+async fn test_next_frame_keeps_target_slots() {
+    // The target is tracked in the second slot
+    let data: [u8; 30] = [
+        0xAA, 0xFF, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x0E, 0x03, 0xB1,
+        0x86, 0x10, 0x00, 0x40, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x55, 0xCC,
+    ];
+    let mut radar = LD2450::new_recycled_config(MockSerial::<0>::new(&data));
+
+    let frame = radar.next_frame().await.unwrap();
+    assert!(frame.targets[0].is_none());
+    assert_eq!(frame.targets[1].as_ref().unwrap().x_coordinate, -782);
+    assert!(frame.targets[2].is_none());
+    assert_eq!(frame.tracked().count(), 1);
+    assert_eq!(frame.timestamp, None);
+}
+
+#[tokio::test]
+async fn test_next_frame_sequence_and_timestamp() {
+    static NOW: AtomicU64 = AtomicU64::new(1000);
+    fn now() -> u64 {
+        NOW.fetch_add(100, Ordering::Relaxed)
+    }
+
+    let data = [&DATA_FRAME[..], &DATA_FRAME, &DATA_FRAME].concat();
+    let mut radar = LD2450::new_recycled_config(MockSerial::<0>::new(&data));
+    radar.set_clock(now);
+
+    let first = radar.next_frame().await.unwrap();
+    assert_eq!((first.sequence, first.timestamp), (0, Some(1000)));
+    // Frames read through next_radar_targets are counted too
+    radar.next_radar_targets().await.unwrap();
+    let third = radar.next_frame().await.unwrap();
+    assert_eq!((third.sequence, third.timestamp), (2, Some(1200)));
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{MockRadar, MockSerialError};
+use hlk_ld2450::{timeout::IdleTimeoutSerial, RadarError, LD2450};
+
+#[derive(Debug)]
+struct TokioDelay;
+
+impl embedded_hal_async::delay::DelayNs for TokioDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        tokio::time::sleep(Duration::from_nanos(ns.into())).await;
+    }
+}
+
+/// A serial port connected to an unplugged radar
+#[derive(Debug)]
+struct Unplugged;
+
+impl embedded_io_async::ErrorType for Unplugged {
+    type Error = MockSerialError;
+}
+
+impl embedded_io_async::Read for Unplugged {
+    async fn read(&mut self, _buf: &mut [u8]) -> Result<usize, Self::Error> {
+        std::future::pending().await
+    }
+}
+
+impl embedded_io_async::Write for Unplugged {
+    async fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        Ok(buf.len())
+    }
+}
+
+#[tokio::test]
+async fn test_next_radar_targets_times_out() {
+    let serial = IdleTimeoutSerial::new(Unplugged, TokioDelay, 10);
+    let mut radar = LD2450::new_recycled_config(serial);
+
+    assert_eq!(radar.next_radar_targets().await, Err(RadarError::Timeout));
+}
+
+#[tokio::test]
+async fn test_enter_config_times_out() {
+    let serial = IdleTimeoutSerial::new(Unplugged, TokioDelay, 10);
+
+    let Err((_, error)) = LD2450::new_recycled_config(serial).enter_config().await else {
+        panic!("the radar is unplugged");
+    };
+    assert_eq!(error, RadarError::Timeout);
+}
+
+#[tokio::test]
+async fn test_responsive_radar_does_not_time_out() {
+    let serial = IdleTimeoutSerial::new(MockRadar::new(), TokioDelay, 10);
+    let mut radar = LD2450::new_recycled_config(serial)
+        .enter_config()
+        .await
+        .unwrap();
+    radar.tracking_mode().await.unwrap();
+
+    let mut radar = radar.exit_config().await.unwrap();
+    assert_eq!(radar.next_radar_targets().await.unwrap().len(), 1);
+}